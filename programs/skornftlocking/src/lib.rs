@@ -1,8 +1,40 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, Discriminator};
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::metadata::mpl_token_metadata::instructions::{
+    FreezeDelegatedAccountCpiBuilder, ThawDelegatedAccountCpiBuilder, TransferV1CpiBuilder,
+};
+use anchor_spl::metadata::mpl_token_metadata::types as metadata_types;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::Token;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    non_transferable::NonTransferable, transfer_hook, BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::{self, onchain::invoke_transfer_checked};
+use anchor_spl::token_interface::{
+    self, Approve, Mint, Revoke, TokenAccount, TokenInterface, TransferChecked,
+};
+use mpl_bubblegum::instructions::TransferCpiBuilder;
+use mpl_bubblegum::programs::{SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+use mpl_bubblegum::utils::get_asset_id;
 
 pub const NFT_LOCK_SEED: &str = "nft_lock";
 pub const VAULT_AUTH_SEED: &str = "nft_vault_authority";
+pub const CONFIG_SEED: &str = "config";
+pub const UNLOCK_PROPOSAL_SEED: &str = "unlock_proposal";
+pub const QUEUED_ACTION_SEED: &str = "queued_action";
+pub const ADMIN_ACTION_SEED: &str = "admin_action";
+
+pub const MAX_SIGNERS: usize = 10;
+pub const MIN_THRESHOLD: u8 = 2;
+pub const MAX_ROLE_GRANTS: usize = 16;
+pub const MAX_TIERS: usize = 16;
+pub const BPS_DENOMINATOR: u16 = 10_000;
+pub const MAX_BATCH_SIZE: usize = 8;
+pub const UNLOCK_PROPOSAL_TTL: i64 = 7 * 86400;
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * 86400;
+pub const MAX_MEMO_LEN: usize = 64;
 
 declare_id!("3CN3wBxMpJnRNndTd8NvghTWRPLbE8c824aBUGBXeSMe");
 
@@ -10,145 +42,3235 @@ declare_id!("3CN3wBxMpJnRNndTd8NvghTWRPLbE8c824aBUGBXeSMe");
 pub mod nftlocking {
     use super::*;
 
-    pub fn lock_nft(ctx: Context<LockNFT>, duration: LockDuration) -> Result<()> {
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        timelock_delay: i64,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            (0..=MAX_TIMELOCK_DELAY).contains(&timelock_delay),
+            LockError::InvalidTimelockDelay
+        );
+        ProgramConfig::validate_signers(&signers, threshold)?;
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.pending_admin = None;
+        config.signers = signers;
+        config.threshold = threshold;
+        config.roles = [Role::Pauser, Role::Unlocker, Role::ConfigManager]
+            .into_iter()
+            .map(|role| RoleGrant {
+                holder: ctx.accounts.admin.key(),
+                role,
+            })
+            .collect();
+        config.tiers = [60, 90, 180, 365]
+            .into_iter()
+            .enumerate()
+            .map(|(id, days)| LockTier {
+                id: id as u8,
+                duration: days * 86400,
+                enabled: true,
+                reward_multiplier_bps: BPS_DENOMINATOR,
+                max_penalty_bps: BPS_DENOMINATOR,
+                requires_cooldown: false,
+            })
+            .collect();
+        config.min_lock_secs = 30 * 86400;
+        config.max_lock_secs = 365 * 86400;
+        config.timelock_delay = timelock_delay;
+        config.action_nonce = 0;
+        config.bump = ctx.bumps.config;
+
+        Ok(())
+    }
+
+    pub fn queue_action(ctx: Context<QueueAction>, action: ConfigAction) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        action.authorize(config, &ctx.accounts.authority.key())?;
+        action.validate()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let queued_action = &mut ctx.accounts.queued_action;
+        queued_action.nonce = config.action_nonce;
+        queued_action.proposer = ctx.accounts.authority.key();
+        queued_action.action = action;
+        queued_action.queued_at = now;
+        queued_action.eta = now + config.timelock_delay;
+        queued_action.bump = ctx.bumps.queued_action;
+
+        config.action_nonce += 1;
+
+        Ok(())
+    }
+
+    pub fn execute_action(ctx: Context<ExecuteAction>) -> Result<()> {
+        let queued_action = &ctx.accounts.queued_action;
+        let config = &mut ctx.accounts.config;
+        queued_action
+            .action
+            .authorize(config, &ctx.accounts.authority.key())?;
+        require!(
+            Clock::get()?.unix_timestamp >= queued_action.eta,
+            LockError::TimelockNotElapsed
+        );
+
+        queued_action.action.apply(config)
+    }
+
+    pub fn cancel_action(_ctx: Context<CancelAction>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn add_tier(
+        ctx: Context<QueueAction>,
+        tier_id: u8,
+        duration: i64,
+        reward_multiplier_bps: u16,
+        max_penalty_bps: u16,
+        requires_cooldown: bool,
+    ) -> Result<()> {
+        let tier = LockTier {
+            id: tier_id,
+            duration,
+            enabled: true,
+            reward_multiplier_bps,
+            max_penalty_bps,
+            requires_cooldown,
+        };
+        queue_action(ctx, ConfigAction::AddTier { tier })
+    }
+
+    pub fn update_tier(
+        ctx: Context<QueueAction>,
+        tier_id: u8,
+        duration: i64,
+        enabled: bool,
+        reward_multiplier_bps: u16,
+        max_penalty_bps: u16,
+        requires_cooldown: bool,
+    ) -> Result<()> {
+        let tier = LockTier {
+            id: tier_id,
+            duration,
+            enabled,
+            reward_multiplier_bps,
+            max_penalty_bps,
+            requires_cooldown,
+        };
+        queue_action(ctx, ConfigAction::UpdateTier { tier })
+    }
+
+    // Disabling only stops new locks on the tier, so it skips the timelock.
+    pub fn disable_tier(ctx: Context<ManageTier>, tier_id: u8) -> Result<()> {
+        ctx.accounts.config.tier_mut(tier_id)?.enabled = false;
+
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let (previous, new_admin) = (config.admin, ctx.accounts.pending_admin.key());
+        config.transfer_grants(&previous, &new_admin)?;
+        config.admin = new_admin;
+        config.pending_admin = None;
+
+        Ok(())
+    }
+
+    pub fn cancel_admin_proposal(ctx: Context<UpdateConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.pending_admin.is_some(), LockError::NoPendingAdmin);
+        config.pending_admin = None;
+
+        Ok(())
+    }
+
+    pub fn propose_admin(ctx: Context<QueueAction>, new_admin: Pubkey) -> Result<()> {
+        queue_action(ctx, ConfigAction::ProposeAdmin { new_admin })
+    }
+
+    pub fn grant_role(ctx: Context<QueueAction>, role: Role, holder: Pubkey) -> Result<()> {
+        queue_action(ctx, ConfigAction::GrantRole { role, holder })
+    }
+
+    // Revoking only removes privileges, so it skips the timelock.
+    pub fn revoke_role(ctx: Context<UpdateConfig>, role: Role, holder: Pubkey) -> Result<()> {
+        ctx.accounts.config.revoke_role(role, &holder)
+    }
+
+    pub fn set_signers(
+        ctx: Context<QueueAction>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        queue_action(ctx, ConfigAction::SetSigners { signers, threshold })
+    }
+
+    pub fn propose_admin_unlock(ctx: Context<ProposeAdminUnlock>) -> Result<()> {
+        require!(
+            !ctx.accounts.lock_account.unlocked,
+            LockError::AlreadyUnlocked
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.lock_account = ctx.accounts.lock_account.key();
+        proposal.lock_start_time = ctx.accounts.lock_account.start_time;
+        proposal.lock_count = ctx.accounts.lock_account.lock_count;
+        proposal.proposer = ctx.accounts.signer.key();
+        proposal.approvals = vec![ctx.accounts.signer.key()];
+        proposal.created_at = now;
+        proposal.expires_at = now + UNLOCK_PROPOSAL_TTL;
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        Ok(())
+    }
+
+    pub fn approve_admin_unlock(ctx: Context<ApproveAdminUnlock>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.executed, LockError::ProposalExecuted);
+        require!(
+            Clock::get()?.unix_timestamp < proposal.expires_at,
+            LockError::ProposalExpired
+        );
+
+        // Drop approvals from removed signers so the list stays within `MAX_SIGNERS`.
+        let config = &ctx.accounts.config;
+        proposal.approvals.retain(|key| config.is_signer(key));
+
+        let signer = ctx.accounts.signer.key();
+        require!(
+            !proposal.approvals.contains(&signer),
+            LockError::AlreadyApproved
+        );
+        proposal.approvals.push(signer);
+
+        Ok(())
+    }
+
+    // A proposal can also be closed once its lock has ended, been re-locked or
+    // been closed, so it does not block a new proposal for the same account.
+    pub fn close_unlock_proposal(ctx: Context<CloseUnlockProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        let lock_info = ctx.accounts.lock_account.to_account_info();
+        let stale = if lock_info.data_is_empty() || *lock_info.owner != crate::ID {
+            true
+        } else {
+            let lock_account =
+                LockAccount::try_deserialize(&mut &lock_info.try_borrow_data()?[..])?;
+            lock_account.unlocked || !proposal.matches(&lock_account)
+        };
+        require!(
+            stale || proposal.executed || Clock::get()?.unix_timestamp >= proposal.expires_at,
+            LockError::ProposalActive
+        );
+
+        Ok(())
+    }
+
+    pub fn set_pause(
+        ctx: Context<SetPause>,
+        locks_paused: bool,
+        unlocks_paused: bool,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.locks_paused = locks_paused;
+        config.unlocks_paused = unlocks_paused;
+
+        Ok(())
+    }
+
+    pub fn set_emergency_release(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.config;
+        config.emergency_release = enabled;
+        config.emergency_enabled_at = if enabled { now } else { 0 };
+
+        emit!(EmergencyReleaseSet {
+            admin: ctx.accounts.admin.key(),
+            enabled,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    pub fn lock_nft<'info>(
+        ctx: Context<'_, '_, '_, 'info, LockNFT<'info>>,
+        tier_id: u8,
+        auto_renew: bool,
+    ) -> Result<()> {
+        let terms = ctx.accounts.config.tier_terms(tier_id, auto_renew)?;
+        ctx.accounts.lock(terms, ctx.remaining_accounts)
+    }
+
+    pub fn lock_nft_custom<'info>(
+        ctx: Context<'_, '_, '_, 'info, LockNFT<'info>>,
+        duration: i64,
+        auto_renew: bool,
+    ) -> Result<()> {
+        let terms = ctx.accounts.config.custom_terms(duration, auto_renew)?;
+        ctx.accounts.lock(terms, ctx.remaining_accounts)
+    }
+
+    // `remaining_accounts` holds one (mint, source ATA, vault ATA, lock PDA)
+    // group per NFT; any invalid group fails the whole batch.
+    pub fn lock_nfts_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LockNFTsBatch<'info>>,
+        tier_id: u8,
+        auto_renew: bool,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.check_locks_open()?;
+
+        let groups = ctx.remaining_accounts.chunks_exact(4);
+        require!(
+            groups.remainder().is_empty() && (1..=MAX_BATCH_SIZE).contains(&groups.len()),
+            LockError::InvalidBatch
+        );
+
+        let user = ctx.accounts.user.key();
+        let now = Clock::get()?.unix_timestamp;
+        for group in groups {
+            let [mint_info, source_info, vault_info, lock_info] = group else {
+                return err!(LockError::InvalidBatch);
+            };
+            let nft_mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+            let source = InterfaceAccount::<TokenAccount>::try_from(source_info)?;
+            let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+            check_transferable(mint_info)?;
+            require!(
+                !has_transfer_hook(mint_info)?,
+                LockError::TransferHookNotSupported
+            );
+            require!(
+                source_info.is_writable && vault_info.is_writable && lock_info.is_writable,
+                LockError::InvalidBatch
+            );
+            require_keys_eq!(source.mint, nft_mint.key(), LockError::InvalidMint);
+            require_keys_eq!(source.owner, user, LockError::InvalidOwner);
+            require_keys_eq!(vault.mint, nft_mint.key(), LockError::InvalidMint);
+            require_keys_eq!(
+                vault.owner,
+                ctx.accounts.vault_authority.key(),
+                LockError::InvalidVault
+            );
+
+            ctx.accounts.init_lock_account(lock_info, &nft_mint.key())?;
+            let mut lock_account = Account::<LockAccount>::try_from(lock_info)?;
+            lock_account.begin(
+                user,
+                nft_mint.key(),
+                AssetStandard::Spl,
+                config.tier_terms(tier_id, auto_renew)?,
+                now,
+            )?;
+            lock_account.exit(&crate::ID)?;
+
+            transfer_nft(
+                &ctx.accounts.token_program.to_account_info(),
+                source_info,
+                mint_info,
+                vault_info,
+                &ctx.accounts.user.to_account_info(),
+                &[],
+                &[],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn lock_for<'info>(
+        ctx: Context<'_, '_, '_, 'info, LockFor<'info>>,
+        tier_id: u8,
+        auto_renew: bool,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.check_locks_open()?;
+        let terms = config.tier_terms(tier_id, auto_renew)?;
+        check_transferable(&ctx.accounts.nft_mint.to_account_info())?;
+
+        ctx.accounts.lock_account.begin(
+            ctx.accounts.beneficiary.key(),
+            ctx.accounts.nft_mint.key(),
+            AssetStandard::Spl,
+            terms,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        transfer_nft(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.sender_nft_account.to_account_info(),
+            &ctx.accounts.nft_mint.to_account_info(),
+            &ctx.accounts.vault_nft_account.to_account_info(),
+            &ctx.accounts.sender.to_account_info(),
+            ctx.remaining_accounts,
+            &[],
+        )
+    }
+
+    pub fn lock_pnft(
+        ctx: Context<LockPNFT>,
+        tier_id: u8,
+        auto_renew: bool,
+        authorization_data: Option<AuthorizationData>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.check_locks_open()?;
+        let terms = config.tier_terms(tier_id, auto_renew)?;
+
+        ctx.accounts.lock_account.begin(
+            ctx.accounts.user.key(),
+            ctx.accounts.pnft.nft_mint.key(),
+            AssetStandard::Programmable,
+            terms,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        ctx.accounts.pnft.transfer(
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.vault_authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            authorization_data,
+            &[],
+        )
+    }
+
+    pub fn lock_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, LockCNFT<'info>>,
+        leaf: CompressedLeaf,
+        tier_id: u8,
+        auto_renew: bool,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.check_locks_open()?;
+        let terms = config.tier_terms(tier_id, auto_renew)?;
+
+        ctx.accounts.lock_account.begin(
+            ctx.accounts.user.key(),
+            get_asset_id(&ctx.accounts.tree.merkle_tree.key(), leaf.nonce),
+            AssetStandard::Compressed,
+            terms,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        ctx.accounts.tree.transfer(
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.leaf_delegate.to_account_info(),
+            &ctx.accounts.vault_authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &leaf,
+            ctx.remaining_accounts,
+            &[],
+        )
+    }
+
+    pub fn lock_core(ctx: Context<LockCore>, tier_id: u8, auto_renew: bool) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.check_locks_open()?;
+        let terms = config.tier_terms(tier_id, auto_renew)?;
+
+        let asset = mpl_core::load(&ctx.accounts.asset)?;
+        require_keys_eq!(
+            asset.owner,
+            ctx.accounts.user.key(),
+            LockError::InvalidOwner
+        );
+
         let lock_account = &mut ctx.accounts.lock_account;
-        lock_account.owner = ctx.accounts.user.key();
-        lock_account.nft_mint = ctx.accounts.nft_mint.key();
-        lock_account.start_time = Clock::get()?.unix_timestamp;
-        lock_account.duration = duration.days() * 86400;
-        lock_account.unlocked = false;
+        lock_account.begin(
+            ctx.accounts.user.key(),
+            ctx.accounts.asset.key(),
+            AssetStandard::Core,
+            terms,
+            Clock::get()?.unix_timestamp,
+        )?;
+        lock_account.custody_mode = CustodyMode::Frozen;
+
+        let vault_authority = ctx.accounts.vault_authority.key();
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        let user = ctx.accounts.user.to_account_info();
+        let core = ctx.accounts.core_accounts();
+        match asset.freeze_delegate {
+            None => core.add_freeze_delegate(&user, vault_authority),
+            Some(mpl_core::PluginAuthority::Address { address }) if address == vault_authority => {
+                core.set_frozen(&ctx.accounts.vault_authority, true, signer)
+            }
+            Some(_) => {
+                core.approve_freeze_delegate(&user, vault_authority)?;
+                core.set_frozen(&ctx.accounts.vault_authority, true, signer)
+            }
+        }
+    }
+
+    pub fn soft_lock_nft(ctx: Context<SoftLockNFT>, tier_id: u8, auto_renew: bool) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.check_locks_open()?;
+        let terms = config.tier_terms(tier_id, auto_renew)?;
+
+        let lock_account = &mut ctx.accounts.lock_account;
+        lock_account.begin(
+            ctx.accounts.user.key(),
+            ctx.accounts.nft_mint.key(),
+            AssetStandard::Spl,
+            terms,
+            Clock::get()?.unix_timestamp,
+        )?;
+        lock_account.custody_mode = CustodyMode::Frozen;
 
         let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_nft_account.to_account_info(),
-                to: ctx.accounts.vault_nft_account.to_account_info(),
+            Approve {
+                to: ctx.accounts.user_nft_account.to_account_info(),
+                delegate: ctx.accounts.vault_authority.to_account_info(),
                 authority: ctx.accounts.user.to_account_info(),
             },
         );
-        token::transfer(cpi_ctx, 1)?;
+        token_interface::approve(cpi_ctx, 1)?;
+
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        FreezeDelegatedAccountCpiBuilder::new(
+            &ctx.accounts.token_metadata_program.to_account_info(),
+        )
+        .delegate(&ctx.accounts.vault_authority.to_account_info())
+        .token_account(&ctx.accounts.user_nft_account.to_account_info())
+        .edition(&ctx.accounts.edition.to_account_info())
+        .mint(&ctx.accounts.nft_mint.to_account_info())
+        .token_program(&ctx.accounts.token_program.to_account_info())
+        .invoke_signed(signer)
+        .map_err(Into::into)
+    }
+
+    pub fn set_auto_renew(ctx: Context<UpdateLock>, enabled: bool) -> Result<()> {
+        let lock_account = &mut ctx.accounts.lock_account;
+        require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+
+        lock_account.set_auto_renew(enabled, Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
+
+    pub fn set_unlock_delegate(ctx: Context<UpdateLock>, delegate: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.lock_account.unlock_delegate = delegate;
+
+        Ok(())
+    }
+
+    pub fn transfer_lock(ctx: Context<TransferLock>) -> Result<()> {
+        let lock_account = &ctx.accounts.lock_account;
+        require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+
+        let new_lock_account = &mut ctx.accounts.new_lock_account;
+        if new_lock_account.owner != Pubkey::default() {
+            require!(new_lock_account.unlocked, LockError::AlreadyLocked);
+        }
+
+        let mut moved = (**lock_account).clone();
+        moved.owner = ctx.accounts.new_owner.key();
+        moved.unlock_delegate = None;
+        moved.lock_count = new_lock_account
+            .lock_count
+            .checked_add(1)
+            .ok_or(LockError::Overflow)?;
+        new_lock_account.set_inner(moved);
+
+        emit!(LockTransferred {
+            nft_mint: lock_account.nft_mint,
+            previous_owner: lock_account.owner,
+            new_owner: new_lock_account.owner,
+            lock_account: new_lock_account.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
         Ok(())
     }
 
-    pub fn unlock_nft(ctx: Context<UnlockNFT>) -> Result<()> {
+    pub fn get_lock_status(ctx: Context<GetLockStatus>) -> Result<LockStatus> {
+        let lock_account = &ctx.accounts.lock_account;
+        let now = Clock::get()?.unix_timestamp;
+        let period = lock_account.current_period(now);
+
+        Ok(LockStatus {
+            owner: lock_account.owner,
+            nft_mint: lock_account.nft_mint,
+            unlocked: lock_account.unlocked,
+            auto_renew: lock_account.auto_renew,
+            current_period: period.index,
+            period_end: period.end,
+            next_rollover: lock_account.auto_renew.then_some(period.end),
+            cooldown_ends_at: (lock_account.unlock_requested_at != 0)
+                .then_some(lock_account.cooldown_ends_at),
+        })
+    }
+
+    pub fn extend_lock(ctx: Context<UpdateLock>, extension: LockExtension) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.check_locks_open()?;
+
         let lock_account = &mut ctx.accounts.lock_account;
         require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
 
         let now = Clock::get()?.unix_timestamp;
+        let previous_tier_id = lock_account.tier_id;
+        let previous_duration = lock_account.duration;
+        lock_account.extend(config, extension, now)?;
+
+        emit!(LockExtended {
+            lock_account: lock_account.key(),
+            previous_tier_id,
+            tier_id: lock_account.tier_id,
+            previous_duration,
+            duration: lock_account.duration,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    pub fn unlock_nft<'info>(ctx: Context<'_, '_, '_, 'info, UnlockNFT<'info>>) -> Result<()> {
+        require!(
+            !ctx.accounts.config.unlocks_paused,
+            LockError::UnlocksPaused
+        );
+
+        let lock_account = &ctx.accounts.lock_account;
+        require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+
+        // Cooldown tiers withdraw through `request_unlock` and `withdraw_nft`.
+        let emergency_release = ctx.accounts.config.emergency_release;
+        if !emergency_release {
+            require!(!lock_account.requires_cooldown, LockError::CooldownRequired);
+        }
+        lock_account.check_releasable(emergency_release, Clock::get()?.unix_timestamp)?;
+
+        ctx.accounts
+            .release(ctx.bumps.vault_authority, ctx.remaining_accounts)
+    }
+
+    pub fn unlock_pnft(
+        ctx: Context<UnlockPNFT>,
+        authorization_data: Option<AuthorizationData>,
+    ) -> Result<()> {
         require!(
-            now >= lock_account.start_time + lock_account.duration,
-            LockError::StillLocked
+            !ctx.accounts.config.unlocks_paused,
+            LockError::UnlocksPaused
         );
 
+        let lock_account = &mut ctx.accounts.lock_account;
+        require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+
+        let now = Clock::get()?.unix_timestamp;
+        lock_account.check_releasable(ctx.accounts.config.emergency_release, now)?;
         lock_account.unlocked = true;
 
         let bump = ctx.bumps.vault_authority;
         let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
         let signer = &[&signer_seeds[..]];
 
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_nft_account.to_account_info(),
-                to: ctx.accounts.user_nft_account.to_account_info(),
-                authority: ctx.accounts.vault_authority.to_account_info(),
-            },
+        ctx.accounts.pnft.transfer(
+            &ctx.accounts.vault_authority.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            authorization_data,
             signer,
+        )
+    }
+
+    pub fn unlock_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnlockCNFT<'info>>,
+        leaf: CompressedLeaf,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.config.unlocks_paused,
+            LockError::UnlocksPaused
         );
-        token::transfer(cpi_ctx, 1)?;
 
-        Ok(())
+        let lock_account = &mut ctx.accounts.lock_account;
+        require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+
+        let now = Clock::get()?.unix_timestamp;
+        lock_account.check_releasable(ctx.accounts.config.emergency_release, now)?;
+        lock_account.unlocked = true;
+
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        let vault_authority = ctx.accounts.vault_authority.to_account_info();
+        ctx.accounts.tree.transfer(
+            &vault_authority,
+            &vault_authority,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &leaf,
+            ctx.remaining_accounts,
+            signer,
+        )
+    }
+
+    pub fn unlock_core(ctx: Context<UnlockCore>) -> Result<()> {
+        require!(
+            !ctx.accounts.config.unlocks_paused,
+            LockError::UnlocksPaused
+        );
+
+        let lock_account = &mut ctx.accounts.lock_account;
+        require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+
+        let now = Clock::get()?.unix_timestamp;
+        lock_account.check_releasable(ctx.accounts.config.emergency_release, now)?;
+        lock_account.unlocked = true;
+
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        // Thaw, then hand the plugin back to the owner.
+        let core = ctx.accounts.core_accounts();
+        core.set_frozen(&ctx.accounts.vault_authority, false, signer)?;
+        core.revoke_freeze_delegate(&ctx.accounts.vault_authority, signer)
     }
 
-    pub fn admin_unlock(ctx: Context<AdminUnlock>) -> Result<()> {
+    pub fn soft_unlock_nft(ctx: Context<SoftUnlockNFT>) -> Result<()> {
+        require!(
+            !ctx.accounts.config.unlocks_paused,
+            LockError::UnlocksPaused
+        );
+
         let lock_account = &mut ctx.accounts.lock_account;
         require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
 
+        let now = Clock::get()?.unix_timestamp;
+        lock_account.check_releasable(ctx.accounts.config.emergency_release, now)?;
         lock_account.unlocked = true;
 
         let bump = ctx.bumps.vault_authority;
         let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
         let signer = &[&signer_seeds[..]];
 
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_nft_account.to_account_info(),
-                to: ctx.accounts.user_nft_account.to_account_info(),
-                authority: ctx.accounts.vault_authority.to_account_info(),
-            },
+        thaw_nft(
+            &ctx.accounts.token_metadata_program.to_account_info(),
+            &ctx.accounts.user_nft_account.to_account_info(),
+            &ctx.accounts.edition.to_account_info(),
+            &ctx.accounts.nft_mint.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.vault_authority.to_account_info(),
             signer,
-        );
-        token::transfer(cpi_ctx, 1)?;
+        )?;
+
+        // Only the owner can revoke; an unlock delegate leaves that to them.
+        if ctx.accounts.owner.is_signer {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Revoke {
+                    source: ctx.accounts.user_nft_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            );
+            token_interface::revoke(cpi_ctx)?;
+        }
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct LockNFT<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-    #[account(
-        init,
-        payer = user,
-        space = 8 + LockAccount::LEN,
-        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.key().as_ref()],
-        bump
-    )]
-    pub lock_account: Account<'info, LockAccount>,
-    #[account(mut)]
-    pub user_nft_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub vault_nft_account: Account<'info, TokenAccount>,
-    pub nft_mint: Account<'info, Mint>,
-    /// CHECK: PDA
-    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
-    pub vault_authority: UncheckedAccount<'info>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
-}
+    // `remaining_accounts` holds one (lock PDA, mint, vault ATA, owner ATA)
+    // group per lock. Locks that are not releasable yet are skipped; invalid groups
+    // fail the whole batch.
+    pub fn unlock_nfts_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnlockNFTsBatch<'info>>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.unlocks_paused, LockError::UnlocksPaused);
 
-#[derive(Accounts)]
-pub struct UnlockNFT<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.key().as_ref()],
-        bump,
-        constraint = lock_account.owner == user.key() @ LockError::Unauthorized
-    )]
-    pub lock_account: Account<'info, LockAccount>,
-    #[account(mut)]
-    pub user_nft_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub vault_nft_account: Account<'info, TokenAccount>,
-    pub nft_mint: Account<'info, Mint>,
-    /// CHECK: PDA
-    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
-    pub vault_authority: UncheckedAccount<'info>,
-    pub token_program: Program<'info, Token>,
-}
+        let groups = ctx.remaining_accounts.chunks_exact(4);
+        require!(
+            groups.remainder().is_empty() && (1..=MAX_BATCH_SIZE).contains(&groups.len()),
+            LockError::InvalidBatch
+        );
+
+        let user = ctx.accounts.user.key();
+        let vault_authority = ctx.accounts.vault_authority.key();
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut released = Vec::new();
+        for group in groups {
+            let [lock_info, mint_info, vault_info, destination_info] = group else {
+                return err!(LockError::InvalidBatch);
+            };
+            require!(
+                lock_info.is_writable && vault_info.is_writable && destination_info.is_writable,
+                LockError::InvalidBatch
+            );
+            let mut lock_account = Account::<LockAccount>::try_from(lock_info)?;
+            let (expected, _) = Pubkey::find_program_address(
+                &[
+                    lock_account.owner.as_ref(),
+                    NFT_LOCK_SEED.as_bytes(),
+                    lock_account.nft_mint.as_ref(),
+                ],
+                &crate::ID,
+            );
+            require_keys_eq!(lock_info.key(), expected, LockError::InvalidLockAccount);
+            require!(lock_account.can_unlock(&user), LockError::Unauthorized);
+            require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+            require!(
+                lock_account.asset_standard == AssetStandard::Spl,
+                LockError::AssetStandardMismatch
+            );
+            require!(
+                lock_account.custody_mode == CustodyMode::Vault,
+                LockError::CustodyModeMismatch
+            );
+
+            require_keys_eq!(
+                mint_info.key(),
+                lock_account.nft_mint,
+                LockError::InvalidMint
+            );
+            require!(
+                !has_transfer_hook(mint_info)?,
+                LockError::TransferHookNotSupported
+            );
+            let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+            require_keys_eq!(vault.mint, lock_account.nft_mint, LockError::InvalidMint);
+            require_keys_eq!(vault.owner, vault_authority, LockError::InvalidVault);
+            InterfaceAccount::<TokenAccount>::try_from(destination_info)?;
+            require_keys_eq!(
+                destination_info.key(),
+                get_associated_token_address_with_program_id(
+                    &lock_account.owner,
+                    &lock_account.nft_mint,
+                    &ctx.accounts.token_program.key(),
+                ),
+                LockError::InvalidOwner
+            );
+
+            if lock_account
+                .check_releasable(config.emergency_release, now)
+                .is_err()
+            {
+                continue;
+            }
+
+            lock_account.unlocked = true;
+            lock_account.exit(&crate::ID)?;
+
+            transfer_nft(
+                &ctx.accounts.token_program.to_account_info(),
+                vault_info,
+                mint_info,
+                destination_info,
+                &ctx.accounts.vault_authority.to_account_info(),
+                &[],
+                signer,
+            )?;
+
+            released.push(lock_info.key());
+        }
+
+        emit!(LocksReleased {
+            user,
+            lock_accounts: released,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    pub fn request_unlock(ctx: Context<UpdateLock>) -> Result<()> {
+        let lock_account = &mut ctx.accounts.lock_account;
+        require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+        require!(
+            lock_account.requires_cooldown,
+            LockError::CooldownNotRequired
+        );
+        require!(
+            lock_account.unlock_requested_at == 0,
+            LockError::UnlockAlreadyRequested
+        );
+        require!(!lock_account.auto_renew, LockError::AutoRenewEnabled);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= lock_account.unlock_time(now), LockError::StillLocked);
+
+        lock_account.unlock_requested_at = now;
+        lock_account.cooldown_ends_at = now + ctx.accounts.config.unlock_cooldown_secs;
+
+        Ok(())
+    }
+
+    pub fn withdraw_nft<'info>(ctx: Context<'_, '_, '_, 'info, UnlockNFT<'info>>) -> Result<()> {
+        require!(
+            !ctx.accounts.config.unlocks_paused,
+            LockError::UnlocksPaused
+        );
+
+        let lock_account = &ctx.accounts.lock_account;
+        require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+
+        let emergency_release = ctx.accounts.config.emergency_release;
+        if !emergency_release {
+            require!(
+                lock_account.requires_cooldown,
+                LockError::CooldownNotRequired
+            );
+        }
+        lock_account.check_releasable(emergency_release, Clock::get()?.unix_timestamp)?;
+
+        ctx.accounts
+            .release(ctx.bumps.vault_authority, ctx.remaining_accounts)
+    }
+
+    pub fn close_lock_account(ctx: Context<CloseLockAccount>) -> Result<()> {
+        require!(ctx.accounts.lock_account.unlocked, LockError::AlreadyLocked);
+
+        Ok(())
+    }
+
+    pub fn migrate_lock_account(ctx: Context<MigrateLockAccount>) -> Result<()> {
+        let lock_info = ctx.accounts.lock_account.to_account_info();
+        require_keys_eq!(*lock_info.owner, crate::ID, LockError::InvalidLockAccount);
+        require!(
+            lock_info
+                .try_borrow_data()?
+                .starts_with(&LockAccount::DISCRIMINATOR),
+            LockError::InvalidLockAccount
+        );
+
+        let new_len = 8 + LockAccount::LEN;
+        if lock_info.data_len() >= new_len {
+            return Ok(());
+        }
+
+        let rent_due = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(lock_info.lamports());
+        if rent_due > 0 {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: lock_info.clone(),
+                },
+            );
+            system_program::transfer(cpi_ctx, rent_due)?;
+        }
+        lock_info.realloc(new_len, true)?;
+
+        Ok(())
+    }
+
+    // Only SPL vault locks support early unlock. pNFT, cNFT, Core and soft
+    // locks wait for expiry or an admin unlock.
+    pub fn early_unlock<'info>(
+        ctx: Context<'_, '_, '_, 'info, EarlyUnlock<'info>>,
+        asset: PenaltyAsset,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.unlocks_paused, LockError::UnlocksPaused);
+        require_keys_neq!(
+            config.treasury,
+            Pubkey::default(),
+            LockError::TreasuryNotSet
+        );
+
+        let lock_account = &mut ctx.accounts.lock_account;
+        require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < lock_account.unlock_time(now), LockError::LockExpired);
+
+        let penalty_bps = config.early_unlock_penalty_bps(lock_account, now)? as u128;
+        let base = match asset {
+            PenaltyAsset::Sol => config.penalty_lamports,
+            PenaltyAsset::Skor => config.penalty_tokens,
+        };
+        let penalty = (base as u128 * penalty_bps / BPS_DENOMINATOR as u128) as u64;
+
+        if penalty > 0 {
+            match asset {
+                PenaltyAsset::Sol => {
+                    let cpi_ctx = CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.user.to_account_info(),
+                            to: ctx.accounts.treasury.to_account_info(),
+                        },
+                    );
+                    system_program::transfer(cpi_ctx, penalty)?;
+                }
+                PenaltyAsset::Skor => {
+                    let user_skor_account = ctx
+                        .accounts
+                        .user_skor_account
+                        .as_ref()
+                        .ok_or(LockError::MissingPenaltyAccount)?;
+                    let treasury_skor_account = ctx
+                        .accounts
+                        .treasury_skor_account
+                        .as_ref()
+                        .ok_or(LockError::MissingPenaltyAccount)?;
+                    require_keys_eq!(
+                        user_skor_account.mint,
+                        config.skor_mint,
+                        LockError::InvalidMint
+                    );
+                    require_keys_eq!(
+                        treasury_skor_account.mint,
+                        config.skor_mint,
+                        LockError::InvalidMint
+                    );
+                    require_keys_eq!(
+                        treasury_skor_account.owner,
+                        config.treasury,
+                        LockError::InvalidOwner
+                    );
+
+                    let skor_mint = ctx
+                        .accounts
+                        .skor_mint
+                        .as_ref()
+                        .ok_or(LockError::MissingPenaltyAccount)?;
+                    let skor_token_program = ctx
+                        .accounts
+                        .skor_token_program
+                        .as_ref()
+                        .ok_or(LockError::MissingPenaltyAccount)?;
+
+                    let cpi_ctx = CpiContext::new(
+                        skor_token_program.to_account_info(),
+                        TransferChecked {
+                            from: user_skor_account.to_account_info(),
+                            mint: skor_mint.to_account_info(),
+                            to: treasury_skor_account.to_account_info(),
+                            authority: ctx.accounts.user.to_account_info(),
+                        },
+                    );
+                    token_interface::transfer_checked(cpi_ctx, penalty, skor_mint.decimals)?;
+                }
+            }
+        }
+
+        lock_account.unlocked = true;
+        lock_account.rewards_forfeited = true;
+
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        transfer_nft(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.vault_nft_account.to_account_info(),
+            &ctx.accounts.nft_mint.to_account_info(),
+            &ctx.accounts.user_nft_account.to_account_info(),
+            &ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+        )?;
+
+        emit!(EarlyUnlocked {
+            lock_account: lock_account.key(),
+            owner: lock_account.owner,
+            asset,
+            penalty,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    pub fn admin_unlock<'info>(
+        ctx: Context<'_, '_, '_, 'info, AdminUnlock<'info>>,
+        reason: AdminUnlockReason,
+        memo: String,
+    ) -> Result<()> {
+        record_admin_unlock(
+            &mut ctx.accounts.config,
+            &mut ctx.accounts.proposal,
+            &mut ctx.accounts.lock_account,
+            &mut ctx.accounts.action_record,
+            ctx.bumps.action_record,
+            ctx.accounts.unlocker.key(),
+            reason,
+            memo,
+        )?;
+
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        match ctx.accounts.lock_account.custody_mode {
+            CustodyMode::Vault => {
+                let vault_nft_account = ctx
+                    .accounts
+                    .vault_nft_account
+                    .as_ref()
+                    .ok_or(LockError::MissingCustodyAccount)?;
+                transfer_nft(
+                    &ctx.accounts.token_program.to_account_info(),
+                    &vault_nft_account.to_account_info(),
+                    &ctx.accounts.nft_mint.to_account_info(),
+                    &ctx.accounts.user_nft_account.to_account_info(),
+                    &ctx.accounts.vault_authority.to_account_info(),
+                    ctx.remaining_accounts,
+                    signer,
+                )
+            }
+            // The delegate approval stays until the owner revokes it.
+            CustodyMode::Frozen => {
+                let edition = ctx
+                    .accounts
+                    .edition
+                    .as_ref()
+                    .ok_or(LockError::MissingCustodyAccount)?;
+                let token_metadata_program = ctx
+                    .accounts
+                    .token_metadata_program
+                    .as_ref()
+                    .ok_or(LockError::MissingCustodyAccount)?;
+                thaw_nft(
+                    &token_metadata_program.to_account_info(),
+                    &ctx.accounts.user_nft_account.to_account_info(),
+                    &edition.to_account_info(),
+                    &ctx.accounts.nft_mint.to_account_info(),
+                    &ctx.accounts.token_program.to_account_info(),
+                    &ctx.accounts.vault_authority.to_account_info(),
+                    signer,
+                )
+            }
+        }
+    }
+
+    pub fn admin_unlock_pnft(
+        ctx: Context<AdminUnlockPNFT>,
+        reason: AdminUnlockReason,
+        memo: String,
+        authorization_data: Option<AuthorizationData>,
+    ) -> Result<()> {
+        record_admin_unlock(
+            &mut ctx.accounts.config,
+            &mut ctx.accounts.proposal,
+            &mut ctx.accounts.lock_account,
+            &mut ctx.accounts.action_record,
+            ctx.bumps.action_record,
+            ctx.accounts.unlocker.key(),
+            reason,
+            memo,
+        )?;
+
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        ctx.accounts.pnft.transfer(
+            &ctx.accounts.vault_authority.to_account_info(),
+            &ctx.accounts.unlocker.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            authorization_data,
+            signer,
+        )
+    }
+
+    pub fn admin_unlock_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, AdminUnlockCNFT<'info>>,
+        reason: AdminUnlockReason,
+        memo: String,
+        leaf: CompressedLeaf,
+    ) -> Result<()> {
+        record_admin_unlock(
+            &mut ctx.accounts.config,
+            &mut ctx.accounts.proposal,
+            &mut ctx.accounts.lock_account,
+            &mut ctx.accounts.action_record,
+            ctx.bumps.action_record,
+            ctx.accounts.unlocker.key(),
+            reason,
+            memo,
+        )?;
+
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        let vault_authority = ctx.accounts.vault_authority.to_account_info();
+        ctx.accounts.tree.transfer(
+            &vault_authority,
+            &vault_authority,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &leaf,
+            ctx.remaining_accounts,
+            signer,
+        )
+    }
+
+    pub fn admin_unlock_core(
+        ctx: Context<AdminUnlockCore>,
+        reason: AdminUnlockReason,
+        memo: String,
+    ) -> Result<()> {
+        record_admin_unlock(
+            &mut ctx.accounts.config,
+            &mut ctx.accounts.proposal,
+            &mut ctx.accounts.lock_account,
+            &mut ctx.accounts.action_record,
+            ctx.bumps.action_record,
+            ctx.accounts.unlocker.key(),
+            reason,
+            memo,
+        )?;
+
+        let bump = ctx.bumps.vault_authority;
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[bump]];
+        let signer = &[&signer_seeds[..]];
+
+        let core = ctx.accounts.core_accounts();
+        core.set_frozen(&ctx.accounts.vault_authority, false, signer)?;
+        core.revoke_freeze_delegate(&ctx.accounts.vault_authority, signer)
+    }
+}
+
+// Checks an approved proposal against the lock, writes the action record and
+// marks the lock released. The caller then hands the NFT back.
+#[allow(clippy::too_many_arguments)]
+fn record_admin_unlock(
+    config: &mut ProgramConfig,
+    proposal: &mut UnlockProposal,
+    lock_account: &mut Account<LockAccount>,
+    record: &mut AdminActionRecord,
+    record_bump: u8,
+    unlocker: Pubkey,
+    reason: AdminUnlockReason,
+    memo: String,
+) -> Result<()> {
+    require!(memo.len() <= MAX_MEMO_LEN, LockError::MemoTooLong);
+    require!(!lock_account.unlocked, LockError::AlreadyUnlocked);
+    require!(!proposal.executed, LockError::ProposalExecuted);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < proposal.expires_at, LockError::ProposalExpired);
+    require!(proposal.matches(lock_account), LockError::ProposalMismatch);
+
+    // Approvals from admins removed since the proposal was made no longer count.
+    let approvals = proposal
+        .approvals
+        .iter()
+        .filter(|key| config.is_signer(key))
+        .count();
+    require!(
+        approvals >= config.threshold as usize,
+        LockError::ThresholdNotMet
+    );
+
+    record.id = config.admin_action_count;
+    record.admin = unlocker;
+    record.lock_account = lock_account.key();
+    record.nft_mint = lock_account.nft_mint;
+    record.reason = reason;
+    record.memo = memo;
+    record.timestamp = now;
+    record.bump = record_bump;
+
+    config.admin_action_count += 1;
+    proposal.executed = true;
+    lock_account.unlocked = true;
+    lock_account.admin_released = true;
+
+    Ok(())
+}
+
+// Thaws a soft-locked token account. Only `vault_authority` can sign this.
+fn thaw_nft<'info>(
+    token_metadata_program: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    vault_authority: &AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    ThawDelegatedAccountCpiBuilder::new(token_metadata_program)
+        .delegate(vault_authority)
+        .token_account(token_account)
+        .edition(edition)
+        .mint(mint)
+        .token_program(token_program)
+        .invoke_signed(signer)
+        .map_err(Into::into)
+}
+
+// Moves one NFT with `transfer_checked` under either token program. Extra
+// accounts required by a Token-2022 transfer hook are picked out of
+// `additional_accounts`.
+fn transfer_nft<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    additional_accounts: &[AccountInfo<'info>],
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    invoke_transfer_checked(
+        token_program.key,
+        from.clone(),
+        mint.clone(),
+        to.clone(),
+        authority.clone(),
+        additional_accounts,
+        1,
+        0,
+        signer,
+    )
+    .map_err(Into::into)
+}
+
+// Rejects Token-2022 mints that could never be withdrawn from the vault.
+fn check_transferable(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner == spl_token_2022::ID {
+        let data = mint.try_borrow_data()?;
+        let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        require!(
+            state.get_extension::<NonTransferable>().is_err(),
+            LockError::NonTransferableMint
+        );
+    }
+    Ok(())
+}
+
+fn has_transfer_hook(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(false);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(transfer_hook::get_program_id(&state).is_some())
+}
+
+// The subset of the Metaplex Core program used to freeze assets in place. The
+// instructions are encoded by hand as no Core client builds against Anchor 0.29.
+pub mod mpl_core {
+    use super::*;
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+    use anchor_lang::solana_program::program::invoke_signed;
+
+    declare_id!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+    const ADD_PLUGIN_V1: u8 = 2;
+    const UPDATE_PLUGIN_V1: u8 = 6;
+    const APPROVE_PLUGIN_AUTHORITY_V1: u8 = 8;
+    const REVOKE_PLUGIN_AUTHORITY_V1: u8 = 10;
+    // Index of FreezeDelegate in both the `Plugin` and `PluginType` enums.
+    const FREEZE_DELEGATE: u8 = 1;
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+    pub enum PluginAuthority {
+        None,
+        Owner,
+        UpdateAuthority,
+        Address { address: Pubkey },
+    }
+
+    #[allow(dead_code)]
+    #[derive(AnchorDeserialize)]
+    enum UpdateAuthority {
+        None,
+        Address(Pubkey),
+        Collection(Pubkey),
+    }
+
+    #[derive(AnchorDeserialize)]
+    struct BaseAsset {
+        _key: u8,
+        owner: Pubkey,
+        _update_authority: UpdateAuthority,
+        _name: String,
+        _uri: String,
+        _seq: Option<u64>,
+    }
+
+    #[derive(AnchorDeserialize)]
+    struct PluginHeader {
+        _key: u8,
+        plugin_registry_offset: u64,
+    }
+
+    #[derive(AnchorDeserialize)]
+    struct RegistryRecord {
+        plugin_type: u8,
+        authority: PluginAuthority,
+        _offset: u64,
+    }
+
+    pub struct CoreAsset {
+        pub owner: Pubkey,
+        pub freeze_delegate: Option<PluginAuthority>,
+    }
+
+    pub fn load(asset: &AccountInfo) -> Result<CoreAsset> {
+        require_keys_eq!(*asset.owner, ID, LockError::InvalidAsset);
+        let data = asset.try_borrow_data()?;
+        // Key::AssetV1
+        require!(data.first() == Some(&1), LockError::InvalidAsset);
+        let mut rest = &data[..];
+        let base = BaseAsset::deserialize(&mut rest).map_err(|_| LockError::InvalidAsset)?;
+
+        let mut freeze_delegate = None;
+        if !rest.is_empty() {
+            let header =
+                PluginHeader::deserialize(&mut rest).map_err(|_| LockError::InvalidAsset)?;
+            let mut registry = data
+                .get(header.plugin_registry_offset as usize + 1..)
+                .ok_or(LockError::InvalidAsset)?;
+            let records = Vec::<RegistryRecord>::deserialize(&mut registry)
+                .map_err(|_| LockError::InvalidAsset)?;
+            freeze_delegate = records
+                .into_iter()
+                .find(|record| record.plugin_type == FREEZE_DELEGATE)
+                .map(|record| record.authority);
+        }
+
+        Ok(CoreAsset {
+            owner: base.owner,
+            freeze_delegate,
+        })
+    }
+
+    // AddPluginV1 { plugin: FreezeDelegate { frozen: true }, init_authority: Some(..) }
+    fn add_freeze_delegate_data(delegate: Pubkey) -> Result<Vec<u8>> {
+        let mut data = vec![ADD_PLUGIN_V1, FREEZE_DELEGATE, true as u8, 1];
+        PluginAuthority::Address { address: delegate }.serialize(&mut data)?;
+        Ok(data)
+    }
+
+    // ApprovePluginAuthorityV1 { plugin_type: FreezeDelegate, new_authority }
+    fn approve_freeze_delegate_data(delegate: Pubkey) -> Result<Vec<u8>> {
+        let mut data = vec![APPROVE_PLUGIN_AUTHORITY_V1, FREEZE_DELEGATE];
+        PluginAuthority::Address { address: delegate }.serialize(&mut data)?;
+        Ok(data)
+    }
+
+    // UpdatePluginV1 { plugin: FreezeDelegate { frozen } }
+    fn set_frozen_data(frozen: bool) -> Vec<u8> {
+        vec![UPDATE_PLUGIN_V1, FREEZE_DELEGATE, frozen as u8]
+    }
+
+    // RevokePluginAuthorityV1 { plugin_type: FreezeDelegate }
+    fn revoke_freeze_delegate_data() -> Vec<u8> {
+        vec![REVOKE_PLUGIN_AUTHORITY_V1, FREEZE_DELEGATE]
+    }
+
+    pub struct CoreAccounts<'a, 'info> {
+        pub core_program: &'a AccountInfo<'info>,
+        pub asset: &'a AccountInfo<'info>,
+        pub collection: Option<&'a AccountInfo<'info>>,
+        pub payer: &'a AccountInfo<'info>,
+        pub system_program: &'a AccountInfo<'info>,
+    }
+
+    impl<'a, 'info> CoreAccounts<'a, 'info> {
+        pub fn add_freeze_delegate(
+            &self,
+            owner: &AccountInfo<'info>,
+            delegate: Pubkey,
+        ) -> Result<()> {
+            self.invoke(add_freeze_delegate_data(delegate)?, owner, &[])
+        }
+
+        pub fn approve_freeze_delegate(
+            &self,
+            owner: &AccountInfo<'info>,
+            delegate: Pubkey,
+        ) -> Result<()> {
+            self.invoke(approve_freeze_delegate_data(delegate)?, owner, &[])
+        }
+
+        pub fn set_frozen(
+            &self,
+            delegate: &AccountInfo<'info>,
+            frozen: bool,
+            signer: &[&[&[u8]]],
+        ) -> Result<()> {
+            self.invoke(set_frozen_data(frozen), delegate, signer)
+        }
+
+        pub fn revoke_freeze_delegate(
+            &self,
+            delegate: &AccountInfo<'info>,
+            signer: &[&[&[u8]]],
+        ) -> Result<()> {
+            self.invoke(revoke_freeze_delegate_data(), delegate, signer)
+        }
+
+        // Optional accounts that are not supplied are passed as the Core program.
+        fn invoke(
+            &self,
+            data: Vec<u8>,
+            authority: &AccountInfo<'info>,
+            signer: &[&[&[u8]]],
+        ) -> Result<()> {
+            let collection = self.collection.unwrap_or(self.core_program);
+            let accounts = vec![
+                AccountMeta::new(self.asset.key(), false),
+                if self.collection.is_some() {
+                    AccountMeta::new(collection.key(), false)
+                } else {
+                    AccountMeta::new_readonly(ID, false)
+                },
+                AccountMeta::new(self.payer.key(), true),
+                AccountMeta::new_readonly(authority.key(), true),
+                AccountMeta::new_readonly(self.system_program.key(), false),
+                AccountMeta::new_readonly(ID, false),
+            ];
+            let ix = Instruction {
+                program_id: ID,
+                accounts,
+                data,
+            };
+            invoke_signed(
+                &ix,
+                &[
+                    self.asset.clone(),
+                    collection.clone(),
+                    self.payer.clone(),
+                    authority.clone(),
+                    self.system_program.clone(),
+                    self.core_program.clone(),
+                ],
+                signer,
+            )
+            .map_err(Into::into)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn string(value: &str) -> Vec<u8> {
+            let mut bytes = (value.len() as u32).to_le_bytes().to_vec();
+            bytes.extend_from_slice(value.as_bytes());
+            bytes
+        }
+
+        // An AssetV1 with a collection update authority, as Core writes it.
+        fn base_asset(owner: &Pubkey) -> Vec<u8> {
+            let mut data = vec![1];
+            data.extend_from_slice(owner.as_ref());
+            data.push(2);
+            data.extend_from_slice(Pubkey::new_unique().as_ref());
+            data.extend(string("Skor #1"));
+            data.extend(string("https://example.com/1.json"));
+            data.push(0);
+            data
+        }
+
+        // Appends a plugin header, the given plugin data and a registry whose
+        // records are (plugin type, authority, offset).
+        fn with_plugins(mut data: Vec<u8>, records: &[(u8, PluginAuthority)]) -> Vec<u8> {
+            let header_offset = data.len();
+            data.push(3);
+            data.extend_from_slice(&0u64.to_le_bytes());
+
+            let mut offsets = Vec::new();
+            for (plugin_type, _) in records {
+                offsets.push(data.len() as u64);
+                data.extend_from_slice(&[*plugin_type, 1]);
+            }
+
+            let registry_offset = data.len() as u64;
+            data[header_offset + 1..header_offset + 9]
+                .copy_from_slice(&registry_offset.to_le_bytes());
+            data.push(4);
+            data.extend_from_slice(&(records.len() as u32).to_le_bytes());
+            for ((plugin_type, authority), offset) in records.iter().zip(offsets) {
+                data.push(*plugin_type);
+                authority.serialize(&mut data).unwrap();
+                data.extend_from_slice(&offset.to_le_bytes());
+            }
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data
+        }
+
+        fn load_bytes(mut data: Vec<u8>, program: &Pubkey) -> Result<CoreAsset> {
+            let key = Pubkey::new_unique();
+            let mut lamports = 0;
+            let info = AccountInfo::new(
+                &key,
+                false,
+                true,
+                &mut lamports,
+                &mut data,
+                program,
+                false,
+                0,
+            );
+            load(&info)
+        }
+
+        #[test]
+        fn load_reads_an_asset_without_plugins() {
+            let owner = Pubkey::new_unique();
+            let asset = load_bytes(base_asset(&owner), &ID).unwrap();
+
+            assert_eq!(asset.owner, owner);
+            assert!(asset.freeze_delegate.is_none());
+        }
+
+        #[test]
+        fn load_finds_the_freeze_delegate_in_the_registry() {
+            let owner = Pubkey::new_unique();
+            let delegate = Pubkey::new_unique();
+            let data = with_plugins(
+                base_asset(&owner),
+                &[
+                    (0, PluginAuthority::UpdateAuthority),
+                    (
+                        FREEZE_DELEGATE,
+                        PluginAuthority::Address { address: delegate },
+                    ),
+                ],
+            );
+            let asset = load_bytes(data, &ID).unwrap();
+
+            assert_eq!(asset.owner, owner);
+            assert!(asset.freeze_delegate == Some(PluginAuthority::Address { address: delegate }));
+        }
+
+        #[test]
+        fn load_reports_no_freeze_delegate_when_only_other_plugins_exist() {
+            let owner = Pubkey::new_unique();
+            let data = with_plugins(base_asset(&owner), &[(0, PluginAuthority::Owner)]);
+
+            assert!(load_bytes(data, &ID).unwrap().freeze_delegate.is_none());
+        }
+
+        #[test]
+        fn load_rejects_foreign_and_non_asset_accounts() {
+            let owner = Pubkey::new_unique();
+            assert_eq!(
+                load_bytes(base_asset(&owner), &Pubkey::new_unique()).err(),
+                Some(LockError::InvalidAsset.into())
+            );
+
+            let mut collection = base_asset(&owner);
+            collection[0] = 5;
+            assert_eq!(
+                load_bytes(collection, &ID).err(),
+                Some(LockError::InvalidAsset.into())
+            );
+        }
+
+        #[test]
+        fn instruction_data_matches_the_core_layout() {
+            let delegate = Pubkey::new_unique();
+            let mut expected = vec![2, 1, 1, 1, 3];
+            expected.extend_from_slice(delegate.as_ref());
+            assert_eq!(add_freeze_delegate_data(delegate).unwrap(), expected);
+
+            let mut expected = vec![8, 1, 3];
+            expected.extend_from_slice(delegate.as_ref());
+            assert_eq!(approve_freeze_delegate_data(delegate).unwrap(), expected);
+
+            assert_eq!(set_frozen_data(true), vec![6, 1, 1]);
+            assert_eq!(set_frozen_data(false), vec![6, 1, 0]);
+            assert_eq!(revoke_freeze_delegate_data(), vec![10, 1]);
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProgramConfig::LEN,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Nftlocking>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ LockError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        has_one = admin @ LockError::Unauthorized
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct QueueAction<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + QueuedAction::LEN,
+        seeds = [QUEUED_ACTION_SEED.as_bytes(), &config.action_nonce.to_le_bytes()],
+        bump
+    )]
+    pub queued_action: Account<'info, QueuedAction>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAction<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    /// CHECK: receives the queued action rent
+    #[account(mut, address = queued_action.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [QUEUED_ACTION_SEED.as_bytes(), &queued_action.nonce.to_le_bytes()],
+        bump = queued_action.bump
+    )]
+    pub queued_action: Account<'info, QueuedAction>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAction<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        has_one = admin @ LockError::Unauthorized
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    /// CHECK: receives the queued action rent
+    #[account(mut, address = queued_action.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [QUEUED_ACTION_SEED.as_bytes(), &queued_action.nonce.to_le_bytes()],
+        bump = queued_action.bump
+    )]
+    pub queued_action: Account<'info, QueuedAction>,
+}
+
+#[derive(Accounts)]
+pub struct ManageTier<'info> {
+    pub config_manager: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        constraint = config.has_role(&config_manager.key(), Role::ConfigManager) @ LockError::MissingConfigManagerRole
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(pending_admin.key()) @ LockError::NotPendingAdmin
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    pub pauser: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        constraint = config.has_role(&pauser.key(), Role::Pauser) @ LockError::MissingPauserRole
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdminUnlock<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        constraint = config.is_signer(&signer.key()) @ LockError::NotSigner
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + UnlockProposal::LEN,
+        seeds = [UNLOCK_PROPOSAL_SEED.as_bytes(), lock_account.key().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, UnlockProposal>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveAdminUnlock<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        constraint = config.is_signer(&signer.key()) @ LockError::NotSigner
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [UNLOCK_PROPOSAL_SEED.as_bytes(), proposal.lock_account.as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, UnlockProposal>,
+}
+
+#[derive(Accounts)]
+pub struct CloseUnlockProposal<'info> {
+    /// CHECK: receives the proposal rent
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [UNLOCK_PROPOSAL_SEED.as_bytes(), proposal.lock_account.as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, UnlockProposal>,
+    /// CHECK: the proposal's lock account, which may already be closed
+    #[account(address = proposal.lock_account)]
+    pub lock_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LockNFT<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LockAccount::LEN,
+        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.key().as_ref()],
+        bump
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        mut,
+        constraint = user_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = user_nft_account.owner == user.key() @ LockError::InvalidOwner
+    )]
+    pub user_nft_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = vault_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = vault_nft_account.owner == vault_authority.key() @ LockError::InvalidVault
+    )]
+    pub vault_nft_account: InterfaceAccount<'info, TokenAccount>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> LockNFT<'info> {
+    fn lock(&mut self, terms: LockTerms, additional_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        self.config.check_locks_open()?;
+        check_transferable(&self.nft_mint.to_account_info())?;
+
+        self.lock_account.begin(
+            self.user.key(),
+            self.nft_mint.key(),
+            AssetStandard::Spl,
+            terms,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        transfer_nft(
+            &self.token_program.to_account_info(),
+            &self.user_nft_account.to_account_info(),
+            &self.nft_mint.to_account_info(),
+            &self.vault_nft_account.to_account_info(),
+            &self.user.to_account_info(),
+            additional_accounts,
+            &[],
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct LockNFTsBatch<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> LockNFTsBatch<'info> {
+    // Mirrors `init_if_needed` for a lock PDA passed through `remaining_accounts`.
+    fn init_lock_account(&self, lock_info: &AccountInfo<'info>, nft_mint: &Pubkey) -> Result<()> {
+        let user = self.user.key();
+        let (expected, bump) = Pubkey::find_program_address(
+            &[user.as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(lock_info.key(), expected, LockError::InvalidLockAccount);
+        if *lock_info.owner == crate::ID {
+            return Ok(());
+        }
+
+        let space = 8 + LockAccount::LEN;
+        let signer_seeds: &[&[u8]] = &[
+            user.as_ref(),
+            NFT_LOCK_SEED.as_bytes(),
+            nft_mint.as_ref(),
+            &[bump],
+        ];
+        let signer = &[signer_seeds];
+        let rent_due = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(lock_info.lamports());
+        if rent_due > 0 {
+            let cpi_ctx = CpiContext::new(
+                self.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: self.user.to_account_info(),
+                    to: lock_info.clone(),
+                },
+            );
+            system_program::transfer(cpi_ctx, rent_due)?;
+        }
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            system_program::Allocate {
+                account_to_allocate: lock_info.clone(),
+            },
+            signer,
+        );
+        system_program::allocate(cpi_ctx, space as u64)?;
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            system_program::Assign {
+                account_to_assign: lock_info.clone(),
+            },
+            signer,
+        );
+        system_program::assign(cpi_ctx, &crate::ID)?;
+
+        lock_info.try_borrow_mut_data()?[..8].copy_from_slice(&LockAccount::DISCRIMINATOR);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LockFor<'info> {
+    pub sender: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: any wallet can be recorded as the lock owner
+    #[account(constraint = beneficiary.key() != Pubkey::default() @ LockError::DefaultOwner)]
+    pub beneficiary: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + LockAccount::LEN,
+        seeds = [beneficiary.key().as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.key().as_ref()],
+        bump
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        mut,
+        constraint = sender_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = sender_nft_account.owner == sender.key() @ LockError::InvalidOwner
+    )]
+    pub sender_nft_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = vault_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = vault_nft_account.owner == vault_authority.key() @ LockError::InvalidVault
+    )]
+    pub vault_nft_account: InterfaceAccount<'info, TokenAccount>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Token Metadata accounts shared by both legs of a pNFT transfer. `source_*`
+// is the account the NFT leaves and `destination_*` the one it arrives in.
+#[derive(Accounts)]
+pub struct ProgrammableTransfer<'info> {
+    /// CHECK: validated by Token Metadata
+    #[account(mut)]
+    pub source_token: UncheckedAccount<'info>,
+    /// CHECK: validated by Token Metadata
+    #[account(mut)]
+    pub source_token_record: UncheckedAccount<'info>,
+    /// CHECK: validated by Token Metadata
+    #[account(mut)]
+    pub destination_token: UncheckedAccount<'info>,
+    /// CHECK: validated by Token Metadata
+    #[account(mut)]
+    pub destination_token_record: UncheckedAccount<'info>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: validated by Token Metadata
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: validated by Token Metadata
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: rule set named in the metadata's programmable config
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by Token Metadata
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ProgrammableTransfer<'info> {
+    fn transfer(
+        &self,
+        authority: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        destination_owner: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        authorization_data: Option<AuthorizationData>,
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        let token_metadata_program = self.token_metadata_program.to_account_info();
+        let mut cpi = TransferV1CpiBuilder::new(&token_metadata_program);
+        if let Some(authorization_data) = authorization_data {
+            cpi.authorization_data(authorization_data.into());
+        }
+        cpi.token(&self.source_token.to_account_info())
+            .token_owner(authority)
+            .destination_token(&self.destination_token.to_account_info())
+            .destination_owner(destination_owner)
+            .mint(&self.nft_mint.to_account_info())
+            .metadata(&self.metadata.to_account_info())
+            .edition(Some(&self.edition.to_account_info()))
+            .token_record(Some(&self.source_token_record.to_account_info()))
+            .destination_token_record(Some(&self.destination_token_record.to_account_info()))
+            .authority(authority)
+            .payer(payer)
+            .system_program(system_program)
+            .sysvar_instructions(&self.sysvar_instructions.to_account_info())
+            .spl_token_program(&self.token_program.to_account_info())
+            .spl_ata_program(&self.associated_token_program.to_account_info())
+            .authorization_rules_program(
+                self.authorization_rules_program.as_ref().map(AsRef::as_ref),
+            )
+            .authorization_rules(self.authorization_rules.as_ref().map(AsRef::as_ref))
+            .amount(1)
+            .invoke_signed(signer)
+            .map_err(Into::into)
+    }
+}
+
+#[derive(Accounts)]
+pub struct LockPNFT<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LockAccount::LEN,
+        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), pnft.nft_mint.key().as_ref()],
+        bump
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        constraint = pnft.source_token.key()
+            == get_associated_token_address_with_program_id(&user.key(), &pnft.nft_mint.key(), &Token::id())
+            @ LockError::InvalidOwner,
+        constraint = pnft.destination_token.key()
+            == get_associated_token_address_with_program_id(&vault_authority.key(), &pnft.nft_mint.key(), &Token::id())
+            @ LockError::InvalidVault
+    )]
+    pub pnft: ProgrammableTransfer<'info>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockPNFT<'info> {
+    #[account(
+        mut,
+        constraint = lock_account.can_unlock(&user.key()) @ LockError::Unauthorized
+    )]
+    pub user: Signer<'info>,
+    /// CHECK: lock owner, bound to the lock account through its seeds
+    pub owner: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [owner.key().as_ref(), NFT_LOCK_SEED.as_bytes(), pnft.nft_mint.key().as_ref()],
+        bump,
+        constraint = lock_account.owner == owner.key() @ LockError::Unauthorized,
+        constraint = lock_account.asset_standard == AssetStandard::Programmable @ LockError::AssetStandardMismatch
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        constraint = pnft.source_token.key()
+            == get_associated_token_address_with_program_id(&vault_authority.key(), &pnft.nft_mint.key(), &Token::id())
+            @ LockError::InvalidVault,
+        constraint = pnft.destination_token.key()
+            == get_associated_token_address_with_program_id(&owner.key(), &pnft.nft_mint.key(), &Token::id())
+            @ LockError::InvalidOwner
+    )]
+    pub pnft: ProgrammableTransfer<'info>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Bubblegum accounts for moving a compressed NFT leaf. The Merkle proof is
+// passed through `remaining_accounts`.
+#[derive(Accounts)]
+pub struct CompressedTransfer<'info> {
+    /// CHECK: validated by Bubblegum
+    pub tree_config: UncheckedAccount<'info>,
+    /// CHECK: validated by Bubblegum
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: SPL Noop program
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    /// CHECK: Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+}
+
+impl<'info> CompressedTransfer<'info> {
+    #[allow(clippy::too_many_arguments)]
+    fn transfer(
+        &self,
+        leaf_owner: &AccountInfo<'info>,
+        leaf_delegate: &AccountInfo<'info>,
+        new_leaf_owner: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        leaf: &CompressedLeaf,
+        proof: &[AccountInfo<'info>],
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        let proof: Vec<_> = proof.iter().map(|node| (node, false, false)).collect();
+
+        TransferCpiBuilder::new(&self.bubblegum_program.to_account_info())
+            .tree_config(&self.tree_config.to_account_info())
+            .leaf_owner(leaf_owner, true)
+            .leaf_delegate(leaf_delegate, false)
+            .new_leaf_owner(new_leaf_owner)
+            .merkle_tree(&self.merkle_tree.to_account_info())
+            .log_wrapper(&self.log_wrapper.to_account_info())
+            .compression_program(&self.compression_program.to_account_info())
+            .system_program(system_program)
+            .root(leaf.root)
+            .data_hash(leaf.data_hash)
+            .creator_hash(leaf.creator_hash)
+            .nonce(leaf.nonce)
+            .index(leaf.index)
+            .add_remaining_accounts(&proof)
+            .invoke_signed(signer)
+            .map_err(Into::into)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(leaf: CompressedLeaf)]
+pub struct LockCNFT<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// CHECK: current leaf delegate, only hashed into the leaf
+    pub leaf_delegate: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LockAccount::LEN,
+        seeds = [
+            user.key().as_ref(),
+            NFT_LOCK_SEED.as_bytes(),
+            get_asset_id(&tree.merkle_tree.key(), leaf.nonce).as_ref()
+        ],
+        bump
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    pub tree: CompressedTransfer<'info>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(leaf: CompressedLeaf)]
+pub struct UnlockCNFT<'info> {
+    #[account(
+        mut,
+        constraint = lock_account.can_unlock(&user.key()) @ LockError::Unauthorized
+    )]
+    pub user: Signer<'info>,
+    /// CHECK: lock owner, bound to the lock account through its seeds
+    pub owner: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [owner.key().as_ref(), NFT_LOCK_SEED.as_bytes(), lock_account.nft_mint.as_ref()],
+        bump,
+        constraint = lock_account.owner == owner.key() @ LockError::Unauthorized,
+        constraint = lock_account.asset_standard == AssetStandard::Compressed @ LockError::AssetStandardMismatch,
+        constraint = lock_account.nft_mint == get_asset_id(&tree.merkle_tree.key(), leaf.nonce) @ LockError::InvalidLockAccount
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    pub tree: CompressedTransfer<'info>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LockCore<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LockAccount::LEN,
+        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), asset.key().as_ref()],
+        bump
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    /// CHECK: owner and layout are checked by `mpl_core::load`
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: validated by Metaplex Core
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Metaplex Core program
+    #[account(address = mpl_core::ID)]
+    pub core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> LockCore<'info> {
+    fn core_accounts(&self) -> mpl_core::CoreAccounts<'_, 'info> {
+        mpl_core::CoreAccounts {
+            core_program: &self.core_program,
+            asset: &self.asset,
+            collection: self.collection.as_ref().map(AsRef::as_ref),
+            payer: &self.user,
+            system_program: &self.system_program,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct UnlockCore<'info> {
+    #[account(
+        mut,
+        constraint = lock_account.can_unlock(&user.key()) @ LockError::Unauthorized
+    )]
+    pub user: Signer<'info>,
+    /// CHECK: lock owner, bound to the lock account through its seeds
+    pub owner: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [owner.key().as_ref(), NFT_LOCK_SEED.as_bytes(), asset.key().as_ref()],
+        bump,
+        constraint = lock_account.owner == owner.key() @ LockError::Unauthorized,
+        constraint = lock_account.asset_standard == AssetStandard::Core @ LockError::AssetStandardMismatch
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    /// CHECK: bound to the lock account through its seeds
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: validated by Metaplex Core
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Metaplex Core program
+    #[account(address = mpl_core::ID)]
+    pub core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UnlockCore<'info> {
+    fn core_accounts(&self) -> mpl_core::CoreAccounts<'_, 'info> {
+        mpl_core::CoreAccounts {
+            core_program: &self.core_program,
+            asset: &self.asset,
+            collection: self.collection.as_ref().map(AsRef::as_ref),
+            payer: &self.user,
+            system_program: &self.system_program,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct SoftLockNFT<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LockAccount::LEN,
+        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.key().as_ref()],
+        bump
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        mut,
+        constraint = user_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = user_nft_account.owner == user.key() @ LockError::InvalidOwner,
+        constraint = user_nft_account.amount == 1 @ LockError::NftNotHeld
+    )]
+    pub user_nft_account: InterfaceAccount<'info, TokenAccount>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: validated by Token Metadata
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SoftUnlockNFT<'info> {
+    #[account(
+        mut,
+        constraint = lock_account.can_unlock(&user.key()) @ LockError::Unauthorized
+    )]
+    pub user: Signer<'info>,
+    /// CHECK: lock owner, bound to the lock account through its seeds
+    pub owner: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [owner.key().as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.key().as_ref()],
+        bump,
+        constraint = lock_account.owner == owner.key() @ LockError::Unauthorized,
+        constraint = lock_account.asset_standard == AssetStandard::Spl @ LockError::AssetStandardMismatch,
+        constraint = lock_account.custody_mode == CustodyMode::Frozen @ LockError::CustodyModeMismatch
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        mut,
+        constraint = user_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = user_nft_account.owner == owner.key() @ LockError::InvalidOwner
+    )]
+    pub user_nft_account: InterfaceAccount<'info, TokenAccount>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: validated by Token Metadata
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLock<'info> {
+    pub user: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), lock_account.nft_mint.as_ref()],
+        bump,
+        constraint = lock_account.owner == user.key() @ LockError::Unauthorized
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+}
+
+#[derive(Accounts)]
+pub struct TransferLock<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// CHECK: any wallet can receive a lock
+    #[account(
+        constraint = new_owner.key() != user.key() @ LockError::SameOwner,
+        constraint = new_owner.key() != Pubkey::default() @ LockError::DefaultOwner
+    )]
+    pub new_owner: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), lock_account.nft_mint.as_ref()],
+        bump,
+        constraint = lock_account.owner == user.key() @ LockError::Unauthorized,
+        constraint = lock_account.custody_mode == CustodyMode::Vault @ LockError::CustodyModeMismatch
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LockAccount::LEN,
+        seeds = [new_owner.key().as_ref(), NFT_LOCK_SEED.as_bytes(), lock_account.nft_mint.as_ref()],
+        bump
+    )]
+    pub new_lock_account: Account<'info, LockAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockNFTsBatch<'info> {
+    pub user: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct GetLockStatus<'info> {
+    pub lock_account: Account<'info, LockAccount>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockNFT<'info> {
+    #[account(
+        mut,
+        constraint = lock_account.can_unlock(&user.key()) @ LockError::Unauthorized
+    )]
+    pub user: Signer<'info>,
+    /// CHECK: lock owner, bound to the lock account through its seeds
+    pub owner: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [owner.key().as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.key().as_ref()],
+        bump,
+        constraint = lock_account.owner == owner.key() @ LockError::Unauthorized,
+        constraint = lock_account.asset_standard == AssetStandard::Spl @ LockError::AssetStandardMismatch,
+        constraint = lock_account.custody_mode == CustodyMode::Vault @ LockError::CustodyModeMismatch
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub user_nft_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = vault_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = vault_nft_account.owner == vault_authority.key() @ LockError::InvalidVault
+    )]
+    pub vault_nft_account: InterfaceAccount<'info, TokenAccount>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> UnlockNFT<'info> {
+    fn release(
+        &mut self,
+        vault_bump: u8,
+        additional_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.lock_account.unlocked = true;
+
+        let signer_seeds = &[VAULT_AUTH_SEED.as_bytes(), &[vault_bump]];
+        let signer = &[&signer_seeds[..]];
+
+        transfer_nft(
+            &self.token_program.to_account_info(),
+            &self.vault_nft_account.to_account_info(),
+            &self.nft_mint.to_account_info(),
+            &self.user_nft_account.to_account_info(),
+            &self.vault_authority.to_account_info(),
+            additional_accounts,
+            signer,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseLockAccount<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), lock_account.nft_mint.as_ref()],
+        bump,
+        constraint = lock_account.owner == user.key() @ LockError::Unauthorized
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+}
+
+#[derive(Accounts)]
+pub struct EarlyUnlock<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [user.key().as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.key().as_ref()],
+        bump,
+        constraint = lock_account.owner == user.key() @ LockError::Unauthorized,
+        constraint = lock_account.asset_standard == AssetStandard::Spl @ LockError::AssetStandardMismatch,
+        constraint = lock_account.custody_mode == CustodyMode::Vault @ LockError::CustodyModeMismatch
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        mut,
+        constraint = user_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = user_nft_account.owner == user.key() @ LockError::InvalidOwner
+    )]
+    pub user_nft_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = vault_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = vault_nft_account.owner == vault_authority.key() @ LockError::InvalidVault
+    )]
+    pub vault_nft_account: InterfaceAccount<'info, TokenAccount>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, address = config.treasury @ LockError::InvalidTreasury)]
+    pub treasury: SystemAccount<'info>,
+    #[account(mut)]
+    pub user_skor_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub treasury_skor_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(address = config.skor_mint @ LockError::InvalidMint)]
+    pub skor_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub skor_token_program: Option<Interface<'info, TokenInterface>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLockAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: owner and discriminator are checked in the handler
+    #[account(mut)]
+    pub lock_account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
 
 #[derive(Accounts)]
 pub struct AdminUnlock<'info> {
-    pub admin: Signer<'info>,
     #[account(mut)]
+    pub unlocker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        constraint = config.has_role(&unlocker.key(), Role::Unlocker) @ LockError::MissingUnlockerRole
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [UNLOCK_PROPOSAL_SEED.as_bytes(), lock_account.key().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, UnlockProposal>,
+    #[account(
+        mut,
+        seeds = [lock_account.owner.as_ref(), NFT_LOCK_SEED.as_bytes(), nft_mint.key().as_ref()],
+        bump,
+        has_one = nft_mint @ LockError::InvalidMint,
+        constraint = lock_account.asset_standard == AssetStandard::Spl @ LockError::AssetStandardMismatch
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        init,
+        payer = unlocker,
+        space = 8 + AdminActionRecord::LEN,
+        seeds = [ADMIN_ACTION_SEED.as_bytes(), &config.admin_action_count.to_le_bytes()],
+        bump
+    )]
+    pub action_record: Account<'info, AdminActionRecord>,
+    #[account(
+        mut,
+        constraint = user_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = user_nft_account.owner == lock_account.owner @ LockError::InvalidOwner
+    )]
+    pub user_nft_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = vault_nft_account.mint == nft_mint.key() @ LockError::InvalidMint,
+        constraint = vault_nft_account.owner == vault_authority.key() @ LockError::InvalidVault
+    )]
+    pub vault_nft_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: validated by Token Metadata
+    pub edition: Option<UncheckedAccount<'info>>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminUnlockPNFT<'info> {
+    #[account(mut)]
+    pub unlocker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        constraint = config.has_role(&unlocker.key(), Role::Unlocker) @ LockError::MissingUnlockerRole
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [UNLOCK_PROPOSAL_SEED.as_bytes(), lock_account.key().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, UnlockProposal>,
+    #[account(
+        mut,
+        seeds = [lock_account.owner.as_ref(), NFT_LOCK_SEED.as_bytes(), pnft.nft_mint.key().as_ref()],
+        bump,
+        constraint = lock_account.asset_standard == AssetStandard::Programmable @ LockError::AssetStandardMismatch
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        init,
+        payer = unlocker,
+        space = 8 + AdminActionRecord::LEN,
+        seeds = [ADMIN_ACTION_SEED.as_bytes(), &config.admin_action_count.to_le_bytes()],
+        bump
+    )]
+    pub action_record: Account<'info, AdminActionRecord>,
+    /// CHECK: lock owner, receives the NFT
+    #[account(address = lock_account.owner @ LockError::InvalidOwner)]
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        constraint = pnft.source_token.key()
+            == get_associated_token_address_with_program_id(&vault_authority.key(), &pnft.nft_mint.key(), &Token::id())
+            @ LockError::InvalidVault,
+        constraint = pnft.destination_token.key()
+            == get_associated_token_address_with_program_id(&owner.key(), &pnft.nft_mint.key(), &Token::id())
+            @ LockError::InvalidOwner
+    )]
+    pub pnft: ProgrammableTransfer<'info>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(reason: AdminUnlockReason, memo: String, leaf: CompressedLeaf)]
+pub struct AdminUnlockCNFT<'info> {
+    #[account(mut)]
+    pub unlocker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        constraint = config.has_role(&unlocker.key(), Role::Unlocker) @ LockError::MissingUnlockerRole
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [UNLOCK_PROPOSAL_SEED.as_bytes(), lock_account.key().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, UnlockProposal>,
+    #[account(
+        mut,
+        seeds = [lock_account.owner.as_ref(), NFT_LOCK_SEED.as_bytes(), lock_account.nft_mint.as_ref()],
+        bump,
+        constraint = lock_account.asset_standard == AssetStandard::Compressed @ LockError::AssetStandardMismatch,
+        constraint = lock_account.nft_mint == get_asset_id(&tree.merkle_tree.key(), leaf.nonce) @ LockError::InvalidLockAccount
+    )]
+    pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        init,
+        payer = unlocker,
+        space = 8 + AdminActionRecord::LEN,
+        seeds = [ADMIN_ACTION_SEED.as_bytes(), &config.admin_action_count.to_le_bytes()],
+        bump
+    )]
+    pub action_record: Account<'info, AdminActionRecord>,
+    /// CHECK: lock owner, receives the leaf
+    #[account(address = lock_account.owner @ LockError::InvalidOwner)]
+    pub owner: UncheckedAccount<'info>,
+    pub tree: CompressedTransfer<'info>,
+    /// CHECK: PDA
+    #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminUnlockCore<'info> {
+    #[account(mut)]
+    pub unlocker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        constraint = config.has_role(&unlocker.key(), Role::Unlocker) @ LockError::MissingUnlockerRole
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [UNLOCK_PROPOSAL_SEED.as_bytes(), lock_account.key().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, UnlockProposal>,
+    #[account(
+        mut,
+        seeds = [lock_account.owner.as_ref(), NFT_LOCK_SEED.as_bytes(), asset.key().as_ref()],
+        bump,
+        constraint = lock_account.asset_standard == AssetStandard::Core @ LockError::AssetStandardMismatch
+    )]
     pub lock_account: Account<'info, LockAccount>,
+    #[account(
+        init,
+        payer = unlocker,
+        space = 8 + AdminActionRecord::LEN,
+        seeds = [ADMIN_ACTION_SEED.as_bytes(), &config.admin_action_count.to_le_bytes()],
+        bump
+    )]
+    pub action_record: Account<'info, AdminActionRecord>,
+    /// CHECK: bound to the lock account through its seeds
     #[account(mut)]
-    pub user_nft_account: Account<'info, TokenAccount>,
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: validated by Metaplex Core
     #[account(mut)]
-    pub vault_nft_account: Account<'info, TokenAccount>,
-    pub nft_mint: Account<'info, Mint>,
+    pub collection: Option<UncheckedAccount<'info>>,
     /// CHECK: PDA
     #[account(seeds = [VAULT_AUTH_SEED.as_bytes()], bump)]
     pub vault_authority: UncheckedAccount<'info>,
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Metaplex Core program
+    #[account(address = mpl_core::ID)]
+    pub core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AdminUnlockCore<'info> {
+    fn core_accounts(&self) -> mpl_core::CoreAccounts<'_, 'info> {
+        mpl_core::CoreAccounts {
+            core_program: &self.core_program,
+            asset: &self.asset,
+            collection: self.collection.as_ref().map(AsRef::as_ref),
+            payer: &self.unlocker,
+            system_program: &self.system_program,
+        }
+    }
+}
+
+#[account]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub roles: Vec<RoleGrant>,
+    pub locks_paused: bool,
+    pub unlocks_paused: bool,
+    pub timelock_delay: i64,
+    pub action_nonce: u64,
+    pub emergency_release: bool,
+    pub emergency_enabled_at: i64,
+    pub admin_action_count: u64,
+    pub tiers: Vec<LockTier>,
+    pub min_lock_secs: i64,
+    pub max_lock_secs: i64,
+    pub treasury: Pubkey,
+    pub skor_mint: Pubkey,
+    pub penalty_lamports: u64,
+    pub penalty_tokens: u64,
+    pub unlock_cooldown_secs: i64,
+    pub bump: u8,
+}
+impl ProgramConfig {
+    pub const LEN: usize = 32
+        + (1 + 32)
+        + (4 + 32 * MAX_SIGNERS)
+        + 1
+        + (4 + RoleGrant::LEN * MAX_ROLE_GRANTS)
+        + 1
+        + 1
+        + 8
+        + 8
+        + 1
+        + 8
+        + 8
+        + (4 + LockTier::LEN * MAX_TIERS)
+        + 8
+        + 8
+        + 32
+        + 32
+        + 8
+        + 8
+        + 8
+        + 1;
+
+    pub fn validate_signers(signers: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !signers.is_empty() && signers.len() <= MAX_SIGNERS,
+            LockError::InvalidSignerSet
+        );
+        require!(
+            threshold >= MIN_THRESHOLD && threshold as usize <= signers.len(),
+            LockError::InvalidThreshold
+        );
+        for (i, signer) in signers.iter().enumerate() {
+            require!(!signers[..i].contains(signer), LockError::InvalidSignerSet);
+        }
+        Ok(())
+    }
+
+    // Moves every role grant and the multisig seat held by `from` to `to`, so
+    // a rotated-out admin key keeps no privileges.
+    pub fn transfer_grants(&mut self, from: &Pubkey, to: &Pubkey) -> Result<()> {
+        let mut roles = Vec::with_capacity(self.roles.len());
+        for grant in self.roles.drain(..) {
+            let holder = if grant.holder == *from {
+                *to
+            } else {
+                grant.holder
+            };
+            if !roles.contains(&RoleGrant {
+                holder,
+                role: grant.role,
+            }) {
+                roles.push(RoleGrant {
+                    holder,
+                    role: grant.role,
+                });
+            }
+        }
+        self.roles = roles;
+
+        if self.is_signer(to) {
+            self.signers.retain(|signer| signer != from);
+        } else if let Some(seat) = self.signers.iter_mut().find(|signer| **signer == *from) {
+            *seat = *to;
+        }
+        require!(
+            self.threshold as usize <= self.signers.len(),
+            LockError::InvalidThreshold
+        );
+        Ok(())
+    }
+
+    pub fn is_signer(&self, key: &Pubkey) -> bool {
+        self.signers.contains(key)
+    }
+
+    pub fn has_role(&self, key: &Pubkey, role: Role) -> bool {
+        self.roles
+            .iter()
+            .any(|grant| grant.holder == *key && grant.role == role)
+    }
+
+    pub fn revoke_role(&mut self, role: Role, holder: &Pubkey) -> Result<()> {
+        require!(self.has_role(holder, role), LockError::RoleNotGranted);
+        self.roles
+            .retain(|grant| !(grant.holder == *holder && grant.role == role));
+        Ok(())
+    }
+
+    pub fn check_locks_open(&self) -> Result<()> {
+        require!(!self.locks_paused, LockError::LocksPaused);
+        require!(!self.emergency_release, LockError::EmergencyReleaseActive);
+        Ok(())
+    }
+
+    pub fn early_unlock_penalty_bps(&self, lock_account: &LockAccount, now: i64) -> Result<u16> {
+        // Locks made before the cap was recorded fall back to their tier's cap.
+        let max_penalty_bps = match (lock_account.max_penalty_bps, lock_account.tier_id) {
+            (Some(max_penalty_bps), _) => max_penalty_bps,
+            (None, Some(tier_id)) => self.tier(tier_id)?.max_penalty_bps,
+            (None, None) => BPS_DENOMINATOR,
+        };
+        let period = lock_account.current_period(now);
+        let remaining = (period.end - now).max(0) as u128;
+        let remaining_bps =
+            remaining * BPS_DENOMINATOR as u128 / (period.end - period.start) as u128;
+
+        Ok(remaining_bps.min(max_penalty_bps as u128) as u16)
+    }
+
+    pub fn tier_terms(&self, tier_id: u8, auto_renew: bool) -> Result<LockTerms> {
+        let tier = self.tier(tier_id)?;
+        require!(tier.enabled, LockError::TierDisabled);
+
+        Ok(LockTerms {
+            duration: tier.duration,
+            tier_id: Some(tier.id),
+            reward_multiplier_bps: tier.reward_multiplier_bps,
+            max_penalty_bps: tier.max_penalty_bps,
+            auto_renew,
+            requires_cooldown: tier.requires_cooldown,
+        })
+    }
+
+    pub fn custom_terms(&self, duration: i64, auto_renew: bool) -> Result<LockTerms> {
+        require!(
+            (self.min_lock_secs..=self.max_lock_secs).contains(&duration),
+            LockError::InvalidDuration
+        );
+
+        Ok(LockTerms {
+            duration,
+            tier_id: None,
+            reward_multiplier_bps: BPS_DENOMINATOR,
+            max_penalty_bps: BPS_DENOMINATOR,
+            auto_renew,
+            requires_cooldown: false,
+        })
+    }
+
+    pub fn tier(&self, tier_id: u8) -> Result<&LockTier> {
+        self.tiers
+            .iter()
+            .find(|tier| tier.id == tier_id)
+            .ok_or_else(|| error!(LockError::TierNotFound))
+    }
+
+    pub fn tier_mut(&mut self, tier_id: u8) -> Result<&mut LockTier> {
+        self.tiers
+            .iter_mut()
+            .find(|tier| tier.id == tier_id)
+            .ok_or_else(|| error!(LockError::TierNotFound))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct LockTier {
+    pub id: u8,
+    pub duration: i64,
+    pub enabled: bool,
+    pub reward_multiplier_bps: u16,
+    pub max_penalty_bps: u16,
+    pub requires_cooldown: bool,
+}
+impl LockTier {
+    pub const LEN: usize = 1 + 8 + 1 + 2 + 2 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct RoleGrant {
+    pub holder: Pubkey,
+    pub role: Role,
+}
+impl RoleGrant {
+    pub const LEN: usize = 32 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Pauser,
+    Unlocker,
+    ConfigManager,
+}
+
+#[account]
+pub struct QueuedAction {
+    pub nonce: u64,
+    pub proposer: Pubkey,
+    pub action: ConfigAction,
+    pub queued_at: i64,
+    pub eta: i64,
+    pub bump: u8,
+}
+impl QueuedAction {
+    pub const LEN: usize = 8 + 32 + ConfigAction::MAX_LEN + 8 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ConfigAction {
+    ProposeAdmin {
+        new_admin: Pubkey,
+    },
+    SetTimelockDelay {
+        delay: i64,
+    },
+    AddTier {
+        tier: LockTier,
+    },
+    UpdateTier {
+        tier: LockTier,
+    },
+    SetLockBounds {
+        min_lock_secs: i64,
+        max_lock_secs: i64,
+    },
+    SetTreasury {
+        treasury: Pubkey,
+        skor_mint: Pubkey,
+    },
+    SetEarlyUnlockPenalty {
+        lamports: u64,
+        tokens: u64,
+    },
+    SetUnlockCooldown {
+        cooldown_secs: i64,
+    },
+    SetSigners {
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+    GrantRole {
+        role: Role,
+        holder: Pubkey,
+    },
+}
+impl ConfigAction {
+    // `SetSigners` is the largest variant.
+    pub const MAX_LEN: usize = 1 + (4 + 32 * MAX_SIGNERS) + 1;
+
+    pub fn authorize(&self, config: &ProgramConfig, key: &Pubkey) -> Result<()> {
+        match self {
+            Self::ProposeAdmin { .. }
+            | Self::SetTimelockDelay { .. }
+            | Self::SetSigners { .. }
+            | Self::GrantRole { .. } => {
+                require_keys_eq!(*key, config.admin, LockError::Unauthorized);
+            }
+            Self::AddTier { .. }
+            | Self::UpdateTier { .. }
+            | Self::SetLockBounds { .. }
+            | Self::SetTreasury { .. }
+            | Self::SetEarlyUnlockPenalty { .. }
+            | Self::SetUnlockCooldown { .. } => {
+                require!(
+                    config.has_role(key, Role::ConfigManager),
+                    LockError::MissingConfigManagerRole
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::ProposeAdmin { .. } => {}
+            Self::SetTimelockDelay { delay } => {
+                require!(
+                    (0..=MAX_TIMELOCK_DELAY).contains(delay),
+                    LockError::InvalidTimelockDelay
+                );
+            }
+            Self::AddTier { tier } | Self::UpdateTier { tier } => {
+                require!(tier.duration > 0, LockError::InvalidDuration);
+                require!(
+                    tier.max_penalty_bps <= BPS_DENOMINATOR,
+                    LockError::InvalidPenalty
+                );
+            }
+            Self::SetLockBounds {
+                min_lock_secs,
+                max_lock_secs,
+            } => {
+                require!(
+                    *min_lock_secs > 0 && min_lock_secs <= max_lock_secs,
+                    LockError::InvalidDuration
+                );
+            }
+            Self::SetUnlockCooldown { cooldown_secs } => {
+                require!(*cooldown_secs >= 0, LockError::InvalidDuration);
+            }
+            Self::SetSigners { signers, threshold } => {
+                ProgramConfig::validate_signers(signers, *threshold)?;
+            }
+            Self::SetTreasury { .. }
+            | Self::SetEarlyUnlockPenalty { .. }
+            | Self::GrantRole { .. } => {}
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, config: &mut ProgramConfig) -> Result<()> {
+        match self {
+            Self::ProposeAdmin { new_admin } => config.pending_admin = Some(*new_admin),
+            Self::SetTimelockDelay { delay } => config.timelock_delay = *delay,
+            Self::AddTier { tier } => {
+                require!(config.tier(tier.id).is_err(), LockError::TierAlreadyExists);
+                require!(config.tiers.len() < MAX_TIERS, LockError::TooManyTiers);
+                config.tiers.push(*tier);
+            }
+            Self::UpdateTier { tier } => *config.tier_mut(tier.id)? = *tier,
+            Self::SetLockBounds {
+                min_lock_secs,
+                max_lock_secs,
+            } => {
+                config.min_lock_secs = *min_lock_secs;
+                config.max_lock_secs = *max_lock_secs;
+            }
+            Self::SetTreasury {
+                treasury,
+                skor_mint,
+            } => {
+                config.treasury = *treasury;
+                config.skor_mint = *skor_mint;
+            }
+            Self::SetEarlyUnlockPenalty { lamports, tokens } => {
+                config.penalty_lamports = *lamports;
+                config.penalty_tokens = *tokens;
+            }
+            Self::SetUnlockCooldown { cooldown_secs } => {
+                config.unlock_cooldown_secs = *cooldown_secs;
+            }
+            Self::SetSigners { signers, threshold } => {
+                config.signers = signers.clone();
+                config.threshold = *threshold;
+            }
+            Self::GrantRole { role, holder } => {
+                require!(
+                    !config.has_role(holder, *role),
+                    LockError::RoleAlreadyGranted
+                );
+                require!(
+                    config.roles.len() < MAX_ROLE_GRANTS,
+                    LockError::TooManyRoleGrants
+                );
+                config.roles.push(RoleGrant {
+                    holder: *holder,
+                    role: *role,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[account]
+pub struct UnlockProposal {
+    pub lock_account: Pubkey,
+    pub lock_start_time: i64,
+    pub lock_count: u32,
+    pub proposer: Pubkey,
+    pub approvals: Vec<Pubkey>,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub executed: bool,
+    pub bump: u8,
+}
+impl UnlockProposal {
+    pub const LEN: usize = 32 + 8 + 4 + 32 + (4 + 32 * MAX_SIGNERS) + 8 + 8 + 1 + 1;
+
+    // A proposal only applies to the lock it was made for, not a later re-lock.
+    pub fn matches(&self, lock_account: &LockAccount) -> bool {
+        self.lock_start_time == lock_account.start_time
+            && self.lock_count == lock_account.lock_count
+    }
+}
+
+#[account]
+pub struct AdminActionRecord {
+    pub id: u64,
+    pub admin: Pubkey,
+    pub lock_account: Pubkey,
+    pub nft_mint: Pubkey,
+    pub reason: AdminUnlockReason,
+    pub memo: String,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+impl AdminActionRecord {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1 + (4 + MAX_MEMO_LEN) + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AdminUnlockReason {
+    UserRequest,
+    LostWalletAccess,
+    SecurityIncident,
+    ProgramSunset,
+    Other,
 }
 
+// Fields are append-only and must decode from zeroed bytes, so that
+// `migrate_lock_account` can grow accounts created by older versions.
 #[account]
 pub struct LockAccount {
     pub owner: Pubkey,
@@ -156,29 +3278,354 @@ pub struct LockAccount {
     pub start_time: i64,
     pub duration: i64,
     pub unlocked: bool,
+    pub admin_released: bool,
+    pub tier_id: Option<u8>,
+    pub reward_multiplier_bps: u16,
+    pub lock_count: u32,
+    pub extended_at: i64,
+    pub rewards_forfeited: bool,
+    pub auto_renew: bool,
+    pub renew_period: i64,
+    pub renewals: u32,
+    pub requires_cooldown: bool,
+    pub unlock_requested_at: i64,
+    pub cooldown_ends_at: i64,
+    pub unlock_delegate: Option<Pubkey>,
+    pub asset_standard: AssetStandard,
+    pub custody_mode: CustodyMode,
+    pub max_penalty_bps: Option<u16>,
 }
 impl LockAccount {
-    pub const LEN: usize = 32 + 32 + 8 + 8 + 1;
+    pub const LEN: usize = 32
+        + 32
+        + 8
+        + 8
+        + 1
+        + 1
+        + (1 + 1)
+        + 2
+        + 4
+        + 8
+        + 1
+        + 1
+        + 8
+        + 4
+        + 1
+        + 8
+        + 8
+        + (1 + 32)
+        + 1
+        + 1
+        + (1 + 2);
+
+    pub fn can_unlock(&self, key: &Pubkey) -> bool {
+        self.owner == *key || self.unlock_delegate == Some(*key)
+    }
+
+    pub fn begin(
+        &mut self,
+        owner: Pubkey,
+        nft_mint: Pubkey,
+        standard: AssetStandard,
+        terms: LockTerms,
+        now: i64,
+    ) -> Result<()> {
+        // A lock account from an earlier, finished lock of the same NFT is reused.
+        if self.owner != Pubkey::default() {
+            require!(self.unlocked, LockError::AlreadyLocked);
+        }
+        self.lock_count = self.lock_count.checked_add(1).ok_or(LockError::Overflow)?;
+        self.owner = owner;
+        self.nft_mint = nft_mint;
+        self.start_time = now;
+        self.duration = terms.duration;
+        self.unlocked = false;
+        self.admin_released = false;
+        self.tier_id = terms.tier_id;
+        self.reward_multiplier_bps = terms.reward_multiplier_bps;
+        self.max_penalty_bps = Some(terms.max_penalty_bps);
+        self.extended_at = 0;
+        self.rewards_forfeited = false;
+        self.auto_renew = terms.auto_renew;
+        self.renew_period = terms.duration;
+        self.renewals = 0;
+        self.requires_cooldown = terms.requires_cooldown;
+        self.unlock_requested_at = 0;
+        self.cooldown_ends_at = 0;
+        self.unlock_delegate = None;
+        self.asset_standard = standard;
+        self.custody_mode = CustodyMode::Vault;
+
+        Ok(())
+    }
+
+    // Once the committed duration has passed, an auto-renewing lock rolls into
+    // consecutive periods of `renew_period` seconds.
+    pub fn current_period(&self, now: i64) -> LockPeriod {
+        let end = self.start_time + self.duration;
+        if !self.auto_renew || self.renew_period <= 0 || now < end {
+            return LockPeriod {
+                index: self.renewals,
+                start: self.start_time,
+                end,
+            };
+        }
+
+        let rolled = (now - end) / self.renew_period + 1;
+        let start = end + (rolled - 1) * self.renew_period;
+        LockPeriod {
+            index: self.renewals + rolled as u32,
+            start,
+            end: start + self.renew_period,
+        }
+    }
+
+    pub fn unlock_time(&self, now: i64) -> i64 {
+        self.current_period(now).end
+    }
+
+    // An extension must push the unlock time out, and it clears any pending
+    // unlock request.
+    pub fn extend(
+        &mut self,
+        config: &ProgramConfig,
+        extension: LockExtension,
+        now: i64,
+    ) -> Result<()> {
+        let previous_unlock_time = self.unlock_time(now);
+        match extension {
+            LockExtension::Tier { tier_id } => {
+                let tier = config.tier(tier_id)?;
+                require!(tier.enabled, LockError::TierDisabled);
+                self.duration = tier.duration;
+                self.tier_id = Some(tier.id);
+                self.reward_multiplier_bps = tier.reward_multiplier_bps;
+                self.max_penalty_bps = Some(tier.max_penalty_bps);
+                self.renew_period = tier.duration;
+                self.requires_cooldown = tier.requires_cooldown;
+            }
+            LockExtension::Duration { additional_secs } => {
+                require!(
+                    (1..=config.max_lock_secs).contains(&additional_secs),
+                    LockError::InvalidDuration
+                );
+                self.duration = self
+                    .duration
+                    .checked_add(additional_secs)
+                    .ok_or(LockError::Overflow)?;
+            }
+        }
+        require!(
+            self.unlock_time(now) > previous_unlock_time,
+            LockError::CannotShortenLock
+        );
+
+        self.extended_at = now;
+        self.unlock_requested_at = 0;
+        self.cooldown_ends_at = 0;
+
+        Ok(())
+    }
+
+    pub fn set_auto_renew(&mut self, enabled: bool, now: i64) {
+        if enabled {
+            if self.renew_period == 0 {
+                self.renew_period = self.duration;
+            }
+            self.unlock_requested_at = 0;
+            self.cooldown_ends_at = 0;
+        } else {
+            // Commit the period in progress so the lock ends when it finishes.
+            let period = self.current_period(now);
+            self.renewals = period.index;
+            self.duration = period.end - self.start_time;
+        }
+        self.auto_renew = enabled;
+    }
+
+    // Cooldown tiers release once the requested cooldown has run out; the
+    // rest once the current period has ended.
+    pub fn check_releasable(&self, emergency_release: bool, now: i64) -> Result<()> {
+        if emergency_release {
+            return Ok(());
+        }
+        require!(!self.auto_renew, LockError::AutoRenewEnabled);
+        if self.requires_cooldown {
+            require!(self.unlock_requested_at != 0, LockError::UnlockNotRequested);
+            require!(now >= self.cooldown_ends_at, LockError::CooldownActive);
+        } else {
+            require!(now >= self.unlock_time(now), LockError::StillLocked);
+        }
+
+        Ok(())
+    }
+}
+
+pub struct LockPeriod {
+    pub index: u32,
+    pub start: i64,
+    pub end: i64,
+}
+
+// Zero must stay `Spl` so migrated accounts decode as plain SPL locks.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssetStandard {
+    #[default]
+    Spl,
+    Programmable,
+    // `nft_mint` holds the Bubblegum asset id.
+    Compressed,
+    // `nft_mint` holds the Metaplex Core asset address.
+    Core,
+}
+
+// Zero must stay `Vault` for the same reason as `AssetStandard`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CustodyMode {
+    #[default]
+    Vault,
+    // The NFT stays in the owner's wallet, delegated to and frozen by
+    // `vault_authority`. Core locks always use this mode.
+    Frozen,
 }
 
+// Token Metadata's `AuthorizationData`, with the payload map as a list so
+// Anchor clients can encode it. Rule sets read it on pNFT transfers.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
-pub enum LockDuration {
-    Sixty,
-    Ninety,
-    OneEighty,
-    ThreeSixtyFive,
-}
-impl LockDuration {
-    pub fn days(&self) -> i64 {
-        match self {
-            Self::Sixty => 60,
-            Self::Ninety => 90,
-            Self::OneEighty => 180,
-            Self::ThreeSixtyFive => 365,
+pub struct AuthorizationData {
+    pub payload: Vec<PayloadEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct PayloadEntry {
+    pub key: String,
+    pub value: PayloadValue,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PayloadValue {
+    Pubkey(Pubkey),
+    Seeds(Vec<Vec<u8>>),
+    MerkleProof {
+        leaf: [u8; 32],
+        proof: Vec<[u8; 32]>,
+    },
+    Number(u64),
+}
+
+impl From<AuthorizationData> for metadata_types::AuthorizationData {
+    fn from(data: AuthorizationData) -> Self {
+        let map = data
+            .payload
+            .into_iter()
+            .map(|entry| {
+                let value = match entry.value {
+                    PayloadValue::Pubkey(key) => metadata_types::PayloadType::Pubkey(key),
+                    PayloadValue::Seeds(seeds) => {
+                        metadata_types::PayloadType::Seeds(metadata_types::SeedsVec { seeds })
+                    }
+                    PayloadValue::MerkleProof { leaf, proof } => {
+                        metadata_types::PayloadType::MerkleProof(metadata_types::LeafInfo {
+                            leaf,
+                            proof,
+                        })
+                    }
+                    PayloadValue::Number(number) => metadata_types::PayloadType::Number(number),
+                };
+                (entry.key, value)
+            })
+            .collect();
+
+        Self {
+            payload: metadata_types::Payload { map },
         }
     }
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct CompressedLeaf {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+pub struct LockTerms {
+    pub duration: i64,
+    pub tier_id: Option<u8>,
+    pub reward_multiplier_bps: u16,
+    pub max_penalty_bps: u16,
+    pub auto_renew: bool,
+    pub requires_cooldown: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct LockStatus {
+    pub owner: Pubkey,
+    pub nft_mint: Pubkey,
+    pub unlocked: bool,
+    pub auto_renew: bool,
+    pub current_period: u32,
+    pub period_end: i64,
+    pub next_rollover: Option<i64>,
+    pub cooldown_ends_at: Option<i64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PenaltyAsset {
+    Sol,
+    Skor,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum LockExtension {
+    Tier { tier_id: u8 },
+    Duration { additional_secs: i64 },
+}
+
+#[event]
+pub struct EmergencyReleaseSet {
+    pub admin: Pubkey,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockExtended {
+    pub lock_account: Pubkey,
+    pub previous_tier_id: Option<u8>,
+    pub tier_id: Option<u8>,
+    pub previous_duration: i64,
+    pub duration: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EarlyUnlocked {
+    pub lock_account: Pubkey,
+    pub owner: Pubkey,
+    pub asset: PenaltyAsset,
+    pub penalty: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockTransferred {
+    pub nft_mint: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub lock_account: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LocksReleased {
+    pub user: Pubkey,
+    pub lock_accounts: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum LockError {
     #[msg("NFT is still locked")]
@@ -187,4 +3634,675 @@ pub enum LockError {
     AlreadyUnlocked,
     #[msg("Unauthorized user")]
     Unauthorized,
+    #[msg("Token account mint does not match the NFT")]
+    InvalidMint,
+    #[msg("Token account owner does not match")]
+    InvalidOwner,
+    #[msg("Vault token account is not owned by the vault authority")]
+    InvalidVault,
+    #[msg("No admin handover is pending")]
+    NoPendingAdmin,
+    #[msg("Signer is not the pending admin")]
+    NotPendingAdmin,
+    #[msg("Signer is not a multisig admin")]
+    NotSigner,
+    #[msg("Invalid multisig admin set")]
+    InvalidSignerSet,
+    #[msg("Invalid multisig threshold")]
+    InvalidThreshold,
+    #[msg("Admin already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal has expired")]
+    ProposalExpired,
+    #[msg("Proposal was already executed")]
+    ProposalExecuted,
+    #[msg("Proposal is still active")]
+    ProposalActive,
+    #[msg("Proposal does not match the lock")]
+    ProposalMismatch,
+    #[msg("Not enough approvals")]
+    ThresholdNotMet,
+    #[msg("Signer does not hold the pauser role")]
+    MissingPauserRole,
+    #[msg("Signer does not hold the unlocker role")]
+    MissingUnlockerRole,
+    #[msg("Signer does not hold the config manager role")]
+    MissingConfigManagerRole,
+    #[msg("Role is already granted")]
+    RoleAlreadyGranted,
+    #[msg("Role is not granted")]
+    RoleNotGranted,
+    #[msg("Too many role grants")]
+    TooManyRoleGrants,
+    #[msg("Locking is paused")]
+    LocksPaused,
+    #[msg("Unlocking is paused")]
+    UnlocksPaused,
+    #[msg("Invalid timelock delay")]
+    InvalidTimelockDelay,
+    #[msg("Timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Emergency release is active")]
+    EmergencyReleaseActive,
+    #[msg("Memo is too long")]
+    MemoTooLong,
+    #[msg("Account is not a lock account")]
+    InvalidLockAccount,
+    #[msg("Lock tier not found")]
+    TierNotFound,
+    #[msg("Lock tier is disabled")]
+    TierDisabled,
+    #[msg("Lock tier already exists")]
+    TierAlreadyExists,
+    #[msg("Too many lock tiers")]
+    TooManyTiers,
+    #[msg("Invalid lock duration")]
+    InvalidDuration,
+    #[msg("NFT is already locked")]
+    AlreadyLocked,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("A lock can only be lengthened")]
+    CannotShortenLock,
+    #[msg("Invalid penalty")]
+    InvalidPenalty,
+    #[msg("Treasury is not configured")]
+    TreasuryNotSet,
+    #[msg("Account is not the configured treasury")]
+    InvalidTreasury,
+    #[msg("Penalty token accounts are required")]
+    MissingPenaltyAccount,
+    #[msg("Lock has already expired")]
+    LockExpired,
+    #[msg("Auto-renew must be turned off before unlocking")]
+    AutoRenewEnabled,
+    #[msg("Lock tier requires request_unlock and a cooldown")]
+    CooldownRequired,
+    #[msg("Lock tier has no unlock cooldown")]
+    CooldownNotRequired,
+    #[msg("Unlock was already requested")]
+    UnlockAlreadyRequested,
+    #[msg("Unlock has not been requested")]
+    UnlockNotRequested,
+    #[msg("Unlock cooldown has not finished")]
+    CooldownActive,
+    #[msg("New owner must differ from the current owner")]
+    SameOwner,
+    #[msg("Invalid batch accounts")]
+    InvalidBatch,
+    #[msg("Mint is non-transferable")]
+    NonTransferableMint,
+    #[msg("Transfer-hook mints cannot be batched")]
+    TransferHookNotSupported,
+    #[msg("Lock was made through a different asset standard")]
+    AssetStandardMismatch,
+    #[msg("Account is not a Metaplex Core asset")]
+    InvalidAsset,
+    #[msg("Lock was made with a different custody mode")]
+    CustodyModeMismatch,
+    #[msg("Accounts for the lock's custody mode are missing")]
+    MissingCustodyAccount,
+    #[msg("Token account does not hold the NFT")]
+    NftNotHeld,
+    #[msg("Lock owner cannot be the default address")]
+    DefaultOwner,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every account here must decode from zeroed bytes, so tests start from that.
+    fn zeroed<T: AnchorDeserialize>(len: usize) -> T {
+        T::deserialize(&mut &vec![0u8; len][..]).unwrap()
+    }
+
+    fn lock(start_time: i64, duration: i64, auto_renew: bool) -> LockAccount {
+        let mut lock_account: LockAccount = zeroed(LockAccount::LEN);
+        lock_account.start_time = start_time;
+        lock_account.duration = duration;
+        lock_account.renew_period = duration;
+        lock_account.auto_renew = auto_renew;
+        lock_account
+    }
+
+    #[test]
+    fn current_period_stays_put_without_auto_renew() {
+        let period = lock(1_000, 100, false).current_period(5_000);
+        assert_eq!((period.index, period.start, period.end), (0, 1_000, 1_100));
+    }
+
+    #[test]
+    fn current_period_rolls_over_at_each_boundary() {
+        let lock_account = lock(1_000, 100, true);
+
+        let period = lock_account.current_period(1_099);
+        assert_eq!((period.index, period.start, period.end), (0, 1_000, 1_100));
+        let period = lock_account.current_period(1_100);
+        assert_eq!((period.index, period.start, period.end), (1, 1_100, 1_200));
+        let period = lock_account.current_period(1_350);
+        assert_eq!((period.index, period.start, period.end), (3, 1_300, 1_400));
+    }
+
+    #[test]
+    fn disabling_auto_renew_commits_the_current_period() {
+        let mut lock_account = lock(1_000, 100, true);
+        lock_account.set_auto_renew(false, 1_350);
+
+        assert!(!lock_account.auto_renew);
+        assert_eq!(lock_account.renewals, 3);
+        assert_eq!(lock_account.duration, 400);
+        assert_eq!(lock_account.unlock_time(1_350), 1_400);
+        assert_eq!(lock_account.unlock_time(9_000), 1_400);
+        assert_eq!(lock_account.current_period(9_000).index, 3);
+    }
+
+    #[test]
+    fn reenabling_auto_renew_continues_from_the_committed_period() {
+        let mut lock_account = lock(1_000, 100, true);
+        lock_account.set_auto_renew(false, 1_350);
+        lock_account.unlock_requested_at = 1_360;
+        lock_account.cooldown_ends_at = 1_460;
+        lock_account.set_auto_renew(true, 1_370);
+
+        assert_eq!(
+            (
+                lock_account.unlock_requested_at,
+                lock_account.cooldown_ends_at
+            ),
+            (0, 0)
+        );
+        let period = lock_account.current_period(1_450);
+        assert_eq!((period.index, period.start, period.end), (4, 1_400, 1_500));
+    }
+
+    fn tier(id: u8, duration: i64, max_penalty_bps: u16) -> LockTier {
+        LockTier {
+            id,
+            duration,
+            enabled: true,
+            reward_multiplier_bps: BPS_DENOMINATOR,
+            max_penalty_bps,
+            requires_cooldown: false,
+        }
+    }
+
+    fn config_with_tier(max_penalty_bps: u16) -> ProgramConfig {
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        config.tiers.push(tier(7, 100, max_penalty_bps));
+        config
+    }
+
+    #[test]
+    fn penalty_scales_with_time_remaining() {
+        let config = config_with_tier(BPS_DENOMINATOR);
+        let lock_account = lock(1_000, 100, false);
+
+        assert_eq!(
+            config.early_unlock_penalty_bps(&lock_account, 1_000),
+            Ok(10_000)
+        );
+        assert_eq!(
+            config.early_unlock_penalty_bps(&lock_account, 1_075),
+            Ok(2_500)
+        );
+        assert_eq!(config.early_unlock_penalty_bps(&lock_account, 1_100), Ok(0));
+        assert_eq!(config.early_unlock_penalty_bps(&lock_account, 9_000), Ok(0));
+    }
+
+    #[test]
+    fn penalty_rounds_down() {
+        let config = config_with_tier(BPS_DENOMINATOR);
+        let lock_account = lock(1_000, 3, false);
+
+        assert_eq!(
+            config.early_unlock_penalty_bps(&lock_account, 1_001),
+            Ok(6_666)
+        );
+    }
+
+    #[test]
+    fn penalty_is_capped_by_the_tier() {
+        let mut config = config_with_tier(5_000);
+        let mut lock_account: LockAccount = zeroed(LockAccount::LEN);
+        lock_account
+            .begin(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                AssetStandard::Spl,
+                config.tier_terms(7, false).unwrap(),
+                1_000,
+            )
+            .unwrap();
+        assert_eq!(lock_account.max_penalty_bps, Some(5_000));
+
+        assert_eq!(
+            config.early_unlock_penalty_bps(&lock_account, 1_000),
+            Ok(5_000)
+        );
+        assert_eq!(
+            config.early_unlock_penalty_bps(&lock_account, 1_075),
+            Ok(2_500)
+        );
+
+        // Later tier changes do not reach existing locks.
+        ConfigAction::UpdateTier {
+            tier: tier(7, 100, 1_000),
+        }
+        .apply(&mut config)
+        .unwrap();
+        assert_eq!(
+            config.early_unlock_penalty_bps(&lock_account, 1_000),
+            Ok(5_000)
+        );
+    }
+
+    #[test]
+    fn penalty_falls_back_to_the_tier_for_older_locks() {
+        let config = config_with_tier(5_000);
+        let mut lock_account = lock(1_000, 100, false);
+        lock_account.tier_id = Some(7);
+
+        assert_eq!(
+            config.early_unlock_penalty_bps(&lock_account, 1_000),
+            Ok(5_000)
+        );
+
+        lock_account.tier_id = Some(8);
+        assert_eq!(
+            config.early_unlock_penalty_bps(&lock_account, 1_000),
+            Err(LockError::TierNotFound.into())
+        );
+    }
+
+    #[test]
+    fn penalty_follows_the_current_renewal_period() {
+        let config = config_with_tier(BPS_DENOMINATOR);
+        let lock_account = lock(1_000, 100, true);
+
+        assert_eq!(
+            config.early_unlock_penalty_bps(&lock_account, 1_150),
+            Ok(5_000)
+        );
+    }
+
+    #[test]
+    fn config_action_validate_rejects_bad_values() {
+        let bad = [
+            (
+                ConfigAction::SetTimelockDelay { delay: -1 },
+                LockError::InvalidTimelockDelay,
+            ),
+            (
+                ConfigAction::SetTimelockDelay {
+                    delay: MAX_TIMELOCK_DELAY + 1,
+                },
+                LockError::InvalidTimelockDelay,
+            ),
+            (
+                ConfigAction::AddTier {
+                    tier: tier(1, 0, 0),
+                },
+                LockError::InvalidDuration,
+            ),
+            (
+                ConfigAction::UpdateTier {
+                    tier: tier(1, 100, BPS_DENOMINATOR + 1),
+                },
+                LockError::InvalidPenalty,
+            ),
+            (
+                ConfigAction::SetLockBounds {
+                    min_lock_secs: 0,
+                    max_lock_secs: 100,
+                },
+                LockError::InvalidDuration,
+            ),
+            (
+                ConfigAction::SetLockBounds {
+                    min_lock_secs: 200,
+                    max_lock_secs: 100,
+                },
+                LockError::InvalidDuration,
+            ),
+            (
+                ConfigAction::SetUnlockCooldown { cooldown_secs: -1 },
+                LockError::InvalidDuration,
+            ),
+            (
+                ConfigAction::SetSigners {
+                    signers: vec![],
+                    threshold: 0,
+                },
+                LockError::InvalidSignerSet,
+            ),
+            (
+                ConfigAction::SetSigners {
+                    signers: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+                    threshold: 1,
+                },
+                LockError::InvalidThreshold,
+            ),
+            (
+                ConfigAction::SetSigners {
+                    signers: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+                    threshold: 3,
+                },
+                LockError::InvalidThreshold,
+            ),
+        ];
+        for (action, error) in bad {
+            assert_eq!(action.validate(), Err(error.into()));
+        }
+
+        let duplicate = Pubkey::new_unique();
+        let action = ConfigAction::SetSigners {
+            signers: vec![duplicate, duplicate],
+            threshold: 2,
+        };
+        assert_eq!(action.validate(), Err(LockError::InvalidSignerSet.into()));
+
+        let action = ConfigAction::SetLockBounds {
+            min_lock_secs: 45 * 86400,
+            max_lock_secs: 45 * 86400,
+        };
+        assert_eq!(action.validate(), Ok(()));
+    }
+
+    #[test]
+    fn config_action_authorize_splits_admin_and_config_manager() {
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        config.admin = Pubkey::new_unique();
+        let manager = Pubkey::new_unique();
+        config.roles.push(RoleGrant {
+            holder: manager,
+            role: Role::ConfigManager,
+        });
+
+        let grant = ConfigAction::GrantRole {
+            role: Role::Pauser,
+            holder: manager,
+        };
+        assert_eq!(grant.authorize(&config, &config.admin), Ok(()));
+        assert_eq!(
+            grant.authorize(&config, &manager),
+            Err(LockError::Unauthorized.into())
+        );
+
+        let add_tier = ConfigAction::AddTier {
+            tier: tier(1, 100, 0),
+        };
+        assert_eq!(add_tier.authorize(&config, &manager), Ok(()));
+        assert_eq!(
+            add_tier.authorize(&config, &config.admin),
+            Err(LockError::MissingConfigManagerRole.into())
+        );
+    }
+
+    #[test]
+    fn config_action_apply_updates_tiers() {
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+
+        let add = ConfigAction::AddTier {
+            tier: tier(1, 100, 0),
+        };
+        add.apply(&mut config).unwrap();
+        assert_eq!(
+            add.apply(&mut config),
+            Err(LockError::TierAlreadyExists.into())
+        );
+
+        ConfigAction::UpdateTier {
+            tier: tier(1, 300, 0),
+        }
+        .apply(&mut config)
+        .unwrap();
+        assert_eq!(config.tier(1).unwrap().duration, 300);
+        let update_missing = ConfigAction::UpdateTier {
+            tier: tier(2, 300, 0),
+        };
+        assert_eq!(
+            update_missing.apply(&mut config),
+            Err(LockError::TierNotFound.into())
+        );
+
+        for id in 2..=MAX_TIERS as u8 {
+            ConfigAction::AddTier {
+                tier: tier(id, 100, 0),
+            }
+            .apply(&mut config)
+            .unwrap();
+        }
+        let add_over_limit = ConfigAction::AddTier {
+            tier: tier(MAX_TIERS as u8 + 1, 100, 0),
+        };
+        assert_eq!(
+            add_over_limit.apply(&mut config),
+            Err(LockError::TooManyTiers.into())
+        );
+    }
+
+    #[test]
+    fn config_action_apply_updates_roles_and_signers() {
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        let holder = Pubkey::new_unique();
+
+        let grant = ConfigAction::GrantRole {
+            role: Role::Unlocker,
+            holder,
+        };
+        grant.apply(&mut config).unwrap();
+        assert!(config.has_role(&holder, Role::Unlocker));
+        assert_eq!(
+            grant.apply(&mut config),
+            Err(LockError::RoleAlreadyGranted.into())
+        );
+
+        config.revoke_role(Role::Unlocker, &holder).unwrap();
+        assert!(!config.has_role(&holder, Role::Unlocker));
+        assert_eq!(
+            config.revoke_role(Role::Unlocker, &holder),
+            Err(LockError::RoleNotGranted.into())
+        );
+
+        let signers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        ConfigAction::SetSigners {
+            signers: signers.clone(),
+            threshold: 2,
+        }
+        .apply(&mut config)
+        .unwrap();
+        assert_eq!((config.signers, config.threshold), (signers, 2));
+    }
+
+    fn grant(holder: Pubkey, role: Role) -> RoleGrant {
+        RoleGrant { holder, role }
+    }
+
+    #[test]
+    fn transfer_grants_moves_roles_and_the_signer_seat() {
+        let (old, new, other, cosigner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        config.roles = vec![
+            grant(old, Role::Pauser),
+            grant(other, Role::Unlocker),
+            grant(old, Role::ConfigManager),
+        ];
+        config.signers = vec![cosigner, old];
+        config.threshold = 2;
+
+        config.transfer_grants(&old, &new).unwrap();
+
+        assert!(
+            config.roles
+                == [
+                    grant(new, Role::Pauser),
+                    grant(other, Role::Unlocker),
+                    grant(new, Role::ConfigManager),
+                ]
+        );
+        assert_eq!(config.signers, vec![cosigner, new]);
+    }
+
+    #[test]
+    fn transfer_grants_drops_duplicate_roles() {
+        let (old, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        config.roles = vec![grant(new, Role::Unlocker), grant(old, Role::Unlocker)];
+
+        config.transfer_grants(&old, &new).unwrap();
+
+        assert!(config.roles == [grant(new, Role::Unlocker)]);
+    }
+
+    #[test]
+    fn transfer_grants_drops_the_seat_when_the_new_admin_already_signs() {
+        let (old, new, cosigner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        config.signers = vec![old, new, cosigner];
+        config.threshold = 2;
+
+        config.transfer_grants(&old, &new).unwrap();
+
+        assert_eq!(config.signers, vec![new, cosigner]);
+    }
+
+    #[test]
+    fn transfer_grants_refuses_to_leave_the_threshold_unreachable() {
+        let (old, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        config.signers = vec![old, new];
+        config.threshold = 2;
+
+        assert_eq!(
+            config.transfer_grants(&old, &new),
+            Err(LockError::InvalidThreshold.into())
+        );
+    }
+
+    #[test]
+    fn transfer_grants_leaves_signers_alone_when_the_old_admin_has_no_seat() {
+        let (old, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let signers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        config.signers = signers.clone();
+        config.threshold = 2;
+
+        config.transfer_grants(&old, &new).unwrap();
+
+        assert_eq!(config.signers, signers);
+    }
+
+    #[test]
+    fn extend_by_duration_checks_the_extension_not_the_total() {
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        config.max_lock_secs = 100;
+        let mut lock_account = lock(1_000, 365, false);
+        lock_account.unlock_requested_at = 1_365;
+        lock_account.cooldown_ends_at = 1_465;
+
+        lock_account
+            .extend(
+                &config,
+                LockExtension::Duration {
+                    additional_secs: 50,
+                },
+                1_010,
+            )
+            .unwrap();
+        assert_eq!(lock_account.duration, 415);
+        assert_eq!(lock_account.extended_at, 1_010);
+        assert_eq!(
+            (
+                lock_account.unlock_requested_at,
+                lock_account.cooldown_ends_at
+            ),
+            (0, 0)
+        );
+
+        for additional_secs in [0, -10, 101] {
+            assert_eq!(
+                lock_account.extend(&config, LockExtension::Duration { additional_secs }, 1_010),
+                Err(LockError::InvalidDuration.into())
+            );
+        }
+    }
+
+    #[test]
+    fn extend_to_a_tier_must_push_out_the_unlock_time() {
+        let mut config: ProgramConfig = zeroed(ProgramConfig::LEN);
+        config.tiers.push(tier(1, 50, 0));
+        config.tiers.push(tier(2, 200, 0));
+        let mut long = tier(3, 300, 2_000);
+        long.requires_cooldown = true;
+        config.tiers.push(long);
+
+        // Mid-way through its fourth period, this lock unlocks at 1_400.
+        let mut lock_account = lock(1_000, 100, true);
+        assert_eq!(lock_account.unlock_time(1_350), 1_400);
+
+        assert_eq!(
+            lock_account.extend(&config, LockExtension::Tier { tier_id: 1 }, 1_350),
+            Err(LockError::CannotShortenLock.into())
+        );
+        // A longer total duration still has to move the current unlock time.
+        let mut lock_account = lock(1_000, 100, true);
+        assert_eq!(
+            lock_account.extend(&config, LockExtension::Tier { tier_id: 2 }, 1_350),
+            Err(LockError::CannotShortenLock.into())
+        );
+
+        let mut lock_account = lock(1_000, 100, true);
+        lock_account
+            .extend(&config, LockExtension::Tier { tier_id: 3 }, 1_350)
+            .unwrap();
+        assert_eq!(lock_account.unlock_time(1_350), 1_600);
+        assert_eq!(lock_account.tier_id, Some(3));
+        assert_eq!(lock_account.max_penalty_bps, Some(2_000));
+        assert!(lock_account.requires_cooldown);
+    }
+
+    #[test]
+    fn authorization_data_converts_to_the_token_metadata_payload() {
+        let destination = Pubkey::new_unique();
+        let data = AuthorizationData {
+            payload: vec![
+                PayloadEntry {
+                    key: "Destination".to_string(),
+                    value: PayloadValue::Pubkey(destination),
+                },
+                PayloadEntry {
+                    key: "DestinationSeeds".to_string(),
+                    value: PayloadValue::Seeds(vec![VAULT_AUTH_SEED.as_bytes().to_vec()]),
+                },
+                PayloadEntry {
+                    key: "Amount".to_string(),
+                    value: PayloadValue::Number(1),
+                },
+            ],
+        };
+
+        let map = metadata_types::AuthorizationData::from(data).payload.map;
+        assert_eq!(map.len(), 3);
+        assert_eq!(
+            map["Destination"],
+            metadata_types::PayloadType::Pubkey(destination)
+        );
+        assert_eq!(
+            map["DestinationSeeds"],
+            metadata_types::PayloadType::Seeds(metadata_types::SeedsVec {
+                seeds: vec![VAULT_AUTH_SEED.as_bytes().to_vec()],
+            })
+        );
+        assert_eq!(map["Amount"], metadata_types::PayloadType::Number(1));
+    }
 }
@@ -3,12 +3,12 @@ import {
   getAssociatedTokenAddressSync,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
-import { Connection, PublicKey } from "@solana/web3.js";
+import { Connection, PublicKey, SystemProgram } from "@solana/web3.js";
 import * as dotenv from "dotenv";
 import { readFile } from "fs/promises";
 import { homedir } from "os";
 import { resolve } from "path";
-import { LockAccount } from "./types";
+import { AdminUnlockReason, LockAccount } from "./types";
 
 dotenv.config();
 
@@ -17,6 +17,17 @@ const PROGRAM_ID = new PublicKey(process.env.PROGRAM_ID!);
 const NFT_MINT = new PublicKey(process.env.NFT_MINT!);
 const NFT_LOCK_SEED = "nft_lock";
 const VAULT_AUTH_SEED = "nft_vault_authority";
+const CONFIG_SEED = "config";
+const UNLOCK_PROPOSAL_SEED = "unlock_proposal";
+const ADMIN_ACTION_SEED = "admin_action";
+
+const REASON_OPTIONS: Record<string, AdminUnlockReason> = {
+  UserRequest: { userRequest: {} },
+  LostWalletAccess: { lostWalletAccess: {} },
+  SecurityIncident: { securityIncident: {} },
+  ProgramSunset: { programSunset: {} },
+  Other: { other: {} },
+};
 
 async function loadKeypair(): Promise<anchor.web3.Keypair> {
   const keypairPath = resolve(homedir(), ".config/solana/id.json");
@@ -42,7 +53,7 @@ async function main() {
   const idl = JSON.parse(idlRaw);
   const program = new anchor.Program(idl, PROGRAM_ID, provider);
 
-  const admin = adminKP.publicKey;
+  const unlocker = adminKP.publicKey;
   const nftMint = NFT_MINT;
 
   // Get user address from environment or prompt
@@ -65,6 +76,34 @@ async function main() {
     program.programId
   );
 
+  const [config] = PublicKey.findProgramAddressSync(
+    [Buffer.from(CONFIG_SEED)],
+    program.programId
+  );
+
+  const [proposal] = PublicKey.findProgramAddressSync(
+    [Buffer.from(UNLOCK_PROPOSAL_SEED), lockAccountPDA.toBuffer()],
+    program.programId
+  );
+
+  // Each admin unlock writes an audit record at the next action index
+  const configAccount = await program.account.programConfig.fetch(config);
+  const actionCount = configAccount.adminActionCount as anchor.BN;
+  const [actionRecord] = PublicKey.findProgramAddressSync(
+    [Buffer.from(ADMIN_ACTION_SEED), actionCount.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  );
+
+  const reasonStr = process.env.UNLOCK_REASON || "UserRequest";
+  const reason = REASON_OPTIONS[reasonStr];
+  if (!reason) {
+    console.error(
+      `❌ Invalid UNLOCK_REASON. Use: ${Object.keys(REASON_OPTIONS).join(", ")}`
+    );
+    return;
+  }
+  const memo = process.env.UNLOCK_MEMO || "";
+
   // Derive token accounts
   const userNftAccount = getAssociatedTokenAddressSync(nftMint, user);
   const vaultNftAccount = getAssociatedTokenAddressSync(
@@ -78,7 +117,8 @@ async function main() {
   console.log("👛 User NFT Account:", userNftAccount.toBase58());
   console.log("🔒 Vault NFT Account:", vaultNftAccount.toBase58());
   console.log("👤 User:", user.toBase58());
-  console.log("👨‍💼 Admin:", admin.toBase58());
+  console.log("👨‍💼 Unlocker:", unlocker.toBase58());
+  console.log("🗳️ Proposal:", proposal.toBase58());
 
   // Check if lock account exists and get its details
   try {
@@ -127,17 +167,43 @@ async function main() {
     return;
   }
 
+  // The unlock needs an approved proposal from the signer set
+  try {
+    const proposalAccount = await program.account.unlockProposal.fetch(
+      proposal
+    );
+    const approvals = (proposalAccount.approvals as PublicKey[]).length;
+    console.log(
+      `🗳️ Proposal approvals: ${approvals}/${configAccount.threshold}`
+    );
+    if (approvals < (configAccount.threshold as number)) {
+      console.log("❌ Proposal has not reached the signer threshold yet.");
+      return;
+    }
+  } catch (error) {
+    console.error(
+      "❌ No unlock proposal found. Run proposeAdminUnlock and collect approvals first."
+    );
+    return;
+  }
+
   try {
     const tx = await program.methods
-      .adminUnlock()
+      .adminUnlock(reason, memo)
       .accounts({
-        admin,
+        unlocker,
+        config,
+        proposal,
         lockAccount: lockAccountPDA,
+        actionRecord,
         userNftAccount,
         vaultNftAccount,
         nftMint,
+        edition: null,
         vaultAuthority,
+        tokenMetadataProgram: null,
         tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
       })
       .rpc();
 
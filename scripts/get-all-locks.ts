@@ -32,7 +32,8 @@ async function main() {
 
   // Get all lock accounts
   const accounts = await connection.getProgramAccounts(PROGRAM_ID, {
-    filters: [{ dataSize: 8 + 32 + 32 + 8 + 8 + 1 }], // LockAccount size
+    // Match the discriminator: LockAccount grows as fields are appended
+    filters: [program.coder.accounts.memcmp("LockAccount")],
   });
 
   console.log(`📦 Found ${accounts.length} lock accounts\n`);
@@ -5,7 +5,7 @@ import * as dotenv from "dotenv";
 import { readFile } from "fs/promises";
 import { homedir } from "os";
 import { resolve } from "path";
-import { LockAccount } from "./types";
+import { LockAccount, LockStatus } from "./types";
 
 dotenv.config();
 
@@ -101,24 +101,50 @@ async function main() {
     console.log("Duration (days):", lockAccount.duration.toNumber() / 86400);
     console.log("Unlocked:", lockAccount.unlocked);
 
-    // Calculate time remaining
+    // The program resolves auto-renew rollover and cooldown on-chain
+    const status = (await program.methods
+      .getLockStatus()
+      .accounts({ lockAccount: lockAccountPDA })
+      .view()) as LockStatus;
     const now = Math.floor(Date.now() / 1000);
-    const unlockTime =
-      lockAccount.startTime.toNumber() + lockAccount.duration.toNumber();
-    const timeRemaining = unlockTime - now;
+    const periodEnd = status.periodEnd.toNumber();
+    const timeRemaining = periodEnd - now;
 
     console.log("\n⏰ Time Analysis:");
     console.log("Current Time:", new Date(now * 1000).toLocaleString());
-    console.log("Unlock Time:", new Date(unlockTime * 1000).toLocaleString());
+    console.log("Current Period:", status.currentPeriod);
+    console.log("Period End:", new Date(periodEnd * 1000).toLocaleString());
+    if (status.nextRollover) {
+      console.log(
+        "Next Rollover:",
+        new Date(status.nextRollover.toNumber() * 1000).toLocaleString()
+      );
+    }
+    if (status.cooldownEndsAt) {
+      console.log(
+        "Cooldown Ends:",
+        new Date(status.cooldownEndsAt.toNumber() * 1000).toLocaleString()
+      );
+    }
 
-    if (lockAccount.unlocked) {
+    if (status.unlocked) {
       console.log("✅ Status: NFT is unlocked");
+    } else if (status.cooldownEndsAt) {
+      console.log(
+        status.cooldownEndsAt.toNumber() > now
+          ? "⏳ Status: Unlock requested - cooldown in progress"
+          : "🔓 Status: Cooldown complete - NFT can be withdrawn"
+      );
     } else if (timeRemaining > 0) {
       const daysRemaining = Math.floor(timeRemaining / 86400);
       const hoursRemaining = Math.floor((timeRemaining % 86400) / 3600);
       const minutesRemaining = Math.floor((timeRemaining % 3600) / 60);
 
-      console.log("🔒 Status: NFT is locked");
+      console.log(
+        status.autoRenew
+          ? "🔁 Status: NFT is locked (auto-renew on)"
+          : "🔒 Status: NFT is locked"
+      );
       console.log(
         `⏳ Time remaining: ${daysRemaining} days, ${hoursRemaining} hours, ${minutesRemaining} minutes`
       );
@@ -17,7 +17,7 @@ import * as dotenv from "dotenv";
 import { readFile } from "fs/promises";
 import { homedir } from "os";
 import { resolve } from "path";
-import { LockAccount, LockDuration } from "./types";
+import { LockAccount } from "./types";
 
 dotenv.config();
 
@@ -26,14 +26,7 @@ const PROGRAM_ID = new PublicKey(process.env.PROGRAM_ID!);
 const NFT_MINT = new PublicKey(process.env.NFT_MINT!);
 const NFT_LOCK_SEED = "nft_lock";
 const VAULT_AUTH_SEED = "nft_vault_authority";
-
-// Duration options
-const DURATION_OPTIONS: Record<string, LockDuration> = {
-  Sixty: { sixty: {} },
-  Ninety: { ninety: {} },
-  OneEighty: { oneEighty: {} },
-  ThreeSixtyFive: { threeSixtyFive: {} },
-};
+const CONFIG_SEED = "config";
 
 async function loadKeypair(): Promise<anchor.web3.Keypair> {
   const keypairPath = resolve(homedir(), ".config/solana/id.json");
@@ -88,6 +81,11 @@ async function main() {
     program.programId
   );
 
+  const [config] = PublicKey.findProgramAddressSync(
+    [Buffer.from(CONFIG_SEED)],
+    program.programId
+  );
+
   // Derive token accounts
   const userNftAccount = getAssociatedTokenAddressSync(nftMint, user);
   const vaultNftAccount = getAssociatedTokenAddressSync(
@@ -132,25 +130,25 @@ async function main() {
     return;
   }
 
-  // Get duration from environment or use default
-  const durationStr = process.env.LOCK_DURATION || "Sixty";
-  const duration =
-    DURATION_OPTIONS[durationStr as keyof typeof DURATION_OPTIONS];
+  // Get tier from environment or use default (tiers 0-3 are 60/90/180/365 days)
+  const tierId = Number(process.env.LOCK_TIER ?? "0");
+  const autoRenew = process.env.AUTO_RENEW === "true";
 
-  if (!duration) {
-    console.error(
-      "❌ Invalid duration. Use: Sixty, Ninety, OneEighty, or ThreeSixtyFive"
-    );
+  if (!Number.isInteger(tierId) || tierId < 0 || tierId > 255) {
+    console.error("❌ Invalid LOCK_TIER. Use a tier id from the config");
     return;
   }
 
-  console.log(`🔒 Locking NFT for ${durationStr} days...`);
+  console.log(
+    `🔒 Locking NFT in tier ${tierId}${autoRenew ? " with auto-renew" : ""}...`
+  );
 
   try {
     const tx = await program.methods
-      .lockNft(duration)
+      .lockNft(tierId, autoRenew)
       .accounts({
         user,
+        config,
         lockAccount: lockAccountPDA,
         userNftAccount,
         vaultNftAccount,
@@ -178,6 +176,9 @@ async function main() {
     );
     console.log("Duration (seconds):", lockAccount.duration.toNumber());
     console.log("Duration (days):", lockAccount.duration.toNumber() / 86400);
+    console.log("Tier:", lockAccount.tierId);
+    console.log("Auto-renew:", lockAccount.autoRenew);
+    console.log("Requires cooldown:", lockAccount.requiresCooldown);
     console.log("Unlocked:", lockAccount.unlocked);
   } catch (error) {
     console.error("❌ Failed to lock NFT:", error);
@@ -6,10 +6,46 @@ export interface LockAccount {
   startTime: anchor.BN;
   duration: anchor.BN;
   unlocked: boolean;
+  adminReleased: boolean;
+  tierId: number | null;
+  rewardMultiplierBps: number;
+  lockCount: number;
+  extendedAt: anchor.BN;
+  rewardsForfeited: boolean;
+  autoRenew: boolean;
+  renewPeriod: anchor.BN;
+  renewals: number;
+  requiresCooldown: boolean;
+  unlockRequestedAt: anchor.BN;
+  cooldownEndsAt: anchor.BN;
+  unlockDelegate: anchor.web3.PublicKey | null;
+  assetStandard: AssetStandard;
+  custodyMode: CustodyMode;
+  maxPenaltyBps: number | null;
 }
 
-export type LockDuration =
-  | { sixty: {} }
-  | { ninety: {} }
-  | { oneEighty: {} }
-  | { threeSixtyFive: {} };
+export interface LockStatus {
+  owner: anchor.web3.PublicKey;
+  nftMint: anchor.web3.PublicKey;
+  unlocked: boolean;
+  autoRenew: boolean;
+  currentPeriod: number;
+  periodEnd: anchor.BN;
+  nextRollover: anchor.BN | null;
+  cooldownEndsAt: anchor.BN | null;
+}
+
+export type AssetStandard =
+  | { spl: {} }
+  | { programmable: {} }
+  | { compressed: {} }
+  | { core: {} };
+
+export type CustodyMode = { vault: {} } | { frozen: {} };
+
+export type AdminUnlockReason =
+  | { userRequest: {} }
+  | { lostWalletAccess: {} }
+  | { securityIncident: {} }
+  | { programSunset: {} }
+  | { other: {} };
@@ -17,6 +17,7 @@ const PROGRAM_ID = new PublicKey(process.env.PROGRAM_ID!);
 const NFT_MINT = new PublicKey(process.env.NFT_MINT!);
 const NFT_LOCK_SEED = "nft_lock";
 const VAULT_AUTH_SEED = "nft_vault_authority";
+const CONFIG_SEED = "config";
 
 async function loadKeypair(): Promise<anchor.web3.Keypair> {
   const keypairPath = resolve(homedir(), ".config/solana/id.json");
@@ -56,6 +57,11 @@ async function main() {
     program.programId
   );
 
+  const [config] = PublicKey.findProgramAddressSync(
+    [Buffer.from(CONFIG_SEED)],
+    program.programId
+  );
+
   // Derive token accounts
   const userNftAccount = getAssociatedTokenAddressSync(nftMint, user);
   const vaultNftAccount = getAssociatedTokenAddressSync(
@@ -91,6 +97,18 @@ async function main() {
       return;
     }
 
+    if (lockAccount.autoRenew) {
+      console.log("❌ Auto-renew is on. Disable it before unlocking.");
+      return;
+    }
+
+    if (lockAccount.requiresCooldown) {
+      console.log(
+        "❌ This tier requires a cooldown. Use requestUnlock, then withdrawNft."
+      );
+      return;
+    }
+
     // Check if lock period has expired
     const now = Math.floor(Date.now() / 1000);
     const unlockTime =
@@ -121,6 +139,8 @@ async function main() {
       .unlockNft()
       .accounts({
         user,
+        owner: user,
+        config,
         lockAccount: lockAccountPDA,
         userNftAccount,
         vaultNftAccount,